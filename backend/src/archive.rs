@@ -0,0 +1,59 @@
+//! Streaming ZIP export of a screenshot folder's contents.
+//!
+//! Unlike the variant/thumbnail caches, an archive is never worth keeping
+//! around on disk: it's built once per request straight into the response
+//! body. Each entry is copied from the source file directly into the
+//! in-progress ZIP stream, so memory stays flat regardless of how many
+//! screenshots - or how large any one of them is - a user exports.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_zip::base::write::ZipFileWriter;
+use async_zip::{Compression, ZipEntryBuilder};
+use tokio::io::AsyncWrite;
+
+use crate::storage::StorageProvider;
+
+/// Write a ZIP archive containing `filenames` (resolved relative to
+/// `screenshots_folder`) into `writer`. Each name is resolved through
+/// `storage`, so a `filenames` entry that escapes `screenshots_folder` via
+/// `..` is rejected rather than streaming an arbitrary file into the
+/// archive - for both the local filesystem and S3. Files that can no longer
+/// be opened (deleted mid-export, etc.) are skipped rather than failing the
+/// whole archive.
+pub async fn write_zip_archive(
+    writer: impl AsyncWrite + Unpin,
+    storage: &Arc<dyn StorageProvider>,
+    screenshots_folder: &Path,
+    filenames: &[String],
+) -> anyhow::Result<()> {
+    let mut zip = ZipFileWriter::with_tokio(writer);
+
+    for filename in filenames {
+        let file_path = match storage.resolve(screenshots_folder, Path::new(filename)).await {
+            Ok(path) => path,
+            _ => {
+                tracing::warn!("Skipping {:?} in archive export: outside screenshots folder", filename);
+                continue;
+            }
+        };
+
+        let stream = match storage.open(&file_path, None).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Skipping {:?} in archive export: {}", file_path, e);
+                continue;
+            }
+        };
+        let mut source = tokio_util::io::StreamReader::new(stream);
+
+        let builder = ZipEntryBuilder::new(filename.clone().into(), Compression::Stored);
+        let mut entry_writer = zip.write_entry_stream(builder).await?;
+        tokio::io::copy(&mut source, &mut entry_writer).await?;
+        entry_writer.close().await?;
+    }
+
+    zip.close().await?;
+    Ok(())
+}
@@ -0,0 +1,166 @@
+//! BlurHash encoding for screenshot placeholders.
+//!
+//! Implements the compact BlurHash string format (https://blurha.sh) so the
+//! gallery can paint a smooth placeholder while the full image loads. Hashes
+//! are cached as a `.blurhash` sidecar file next to the source screenshot so
+//! they're only computed once.
+
+use std::path::{Path, PathBuf};
+
+const BASE83_CHARS: &[u8] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz#$%*+,-.:;=?@[]^_{|}~";
+
+fn encode_base83(mut value: u32, length: usize) -> String {
+    let mut digits = vec![0u8; length];
+    for i in (0..length).rev() {
+        digits[i] = BASE83_CHARS[(value % 83) as usize];
+        value /= 83;
+    }
+    String::from_utf8(digits).unwrap()
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let v = value as f32 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let out = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (out * 255.0).round().clamp(0.0, 255.0) as u8
+}
+
+fn sign_pow(value: f32, exp: f32) -> f32 {
+    value.abs().powf(exp).copysign(value)
+}
+
+/// Encode an image, given as row-major linear-RGB pixels in `0.0..=1.0`,
+/// into a BlurHash string with the given number of horizontal/vertical
+/// components (each in `1..=9`).
+pub fn encode(pixels: &[[f32; 3]], width: usize, height: usize, components_x: u32, components_y: u32) -> String {
+    let mut factors: Vec<[f32; 3]> = Vec::with_capacity((components_x * components_y) as usize);
+
+    for j in 0..components_y {
+        for i in 0..components_x {
+            let normalization = if i == 0 && j == 0 { 1.0 } else { 2.0 };
+            let mut r = 0.0f32;
+            let mut g = 0.0f32;
+            let mut b = 0.0f32;
+
+            for y in 0..height {
+                for x in 0..width {
+                    let basis = (std::f32::consts::PI * i as f32 * x as f32 / width as f32).cos()
+                        * (std::f32::consts::PI * j as f32 * y as f32 / height as f32).cos();
+                    let pixel = pixels[y * width + x];
+                    r += basis * pixel[0];
+                    g += basis * pixel[1];
+                    b += basis * pixel[2];
+                }
+            }
+
+            let scale = normalization / (width * height) as f32;
+            factors.push([r * scale, g * scale, b * scale]);
+        }
+    }
+
+    let dc = factors[0];
+    let ac = &factors[1..];
+
+    let mut result = String::new();
+
+    let size_flag = (components_x - 1) + (components_y - 1) * 9;
+    result.push_str(&encode_base83(size_flag, 1));
+
+    let max_ac = ac
+        .iter()
+        .flat_map(|c| c.iter().map(|v| v.abs()))
+        .fold(0.0f32, f32::max);
+
+    let quantized_max_ac = if !ac.is_empty() {
+        ((max_ac * 166.0 - 0.5).clamp(0.0, 82.0)) as u32
+    } else {
+        0
+    };
+    let actual_max_ac = (quantized_max_ac as f32 + 1.0) / 166.0;
+    result.push_str(&encode_base83(quantized_max_ac, 1));
+
+    let dc_value = (encode_component(dc[0]) << 16) | (encode_component(dc[1]) << 8) | encode_component(dc[2]);
+    result.push_str(&encode_base83(dc_value, 4));
+
+    for component in ac {
+        let quantized = component.map(|c| {
+            (sign_pow(c / actual_max_ac, 0.5) * 9.0 + 9.5)
+                .clamp(0.0, 18.0) as u32
+        });
+        let value = quantized[0] * 19 * 19 + quantized[1] * 19 + quantized[2];
+        result.push_str(&encode_base83(value, 2));
+    }
+
+    result
+}
+
+fn encode_component(value: f32) -> u32 {
+    (linear_to_srgb(value)) as u32
+}
+
+/// Decode and heavily downscale an image, convert to linear RGB, and
+/// compute its BlurHash.
+pub fn hash_image(source_bytes: &[u8], components_x: u32, components_y: u32) -> anyhow::Result<String> {
+    let img = image::load_from_memory(source_bytes)?;
+    // Downscaling first keeps the O(width*height*componentsX*componentsY)
+    // basis-function sum cheap; BlurHash only needs a coarse sample.
+    let small = img.resize(64, 64, image::imageops::FilterType::Triangle).to_rgb8();
+    let (width, height) = (small.width() as usize, small.height() as usize);
+
+    let pixels: Vec<[f32; 3]> = small
+        .pixels()
+        .map(|p| {
+            [
+                srgb_to_linear(p[0]),
+                srgb_to_linear(p[1]),
+                srgb_to_linear(p[2]),
+            ]
+        })
+        .collect();
+
+    Ok(encode(&pixels, width, height, components_x, components_y))
+}
+
+fn sidecar_path(source: &Path) -> PathBuf {
+    let mut sidecar = source.as_os_str().to_owned();
+    sidecar.push(".blurhash");
+    PathBuf::from(sidecar)
+}
+
+/// Return the cached BlurHash for `source` if its sidecar file exists and is
+/// newer than the source, otherwise compute and cache a new one. Reads of
+/// both the source image and the sidecar go through `storage`, so this also
+/// works against the S3 backend.
+pub async fn get_or_compute(storage: &dyn crate::storage::StorageProvider, source: &Path) -> anyhow::Result<String> {
+    let sidecar = sidecar_path(source);
+
+    if let (Ok(source_meta), Ok(sidecar_meta)) = (storage.stat(source).await, storage.stat(&sidecar).await) {
+        if sidecar_meta.modified >= source_meta.modified {
+            if let Ok(cached) = crate::storage::read_all(storage, &sidecar).await {
+                if let Ok(cached) = String::from_utf8(cached) {
+                    return Ok(cached);
+                }
+            }
+        }
+    }
+
+    let source_bytes = crate::storage::read_all(storage, source).await?;
+    let hash = tokio::task::spawn_blocking(move || hash_image(&source_bytes, 4, 3)).await??;
+
+    let _ = storage.put(&sidecar, hash.as_bytes()).await;
+
+    Ok(hash)
+}
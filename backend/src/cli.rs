@@ -0,0 +1,137 @@
+//! Offline one-shot CLI commands, for running the binary without starting the
+//! HTTP server (e.g. scripted backups, or debugging what Steam data the
+//! plugin sees). Mirrors the way overcast exposes `add`/`list` subcommands
+//! alongside its normal long-running mode.
+//!
+//! When no subcommand is given, `main()` falls back to the regular server
+//! behavior so existing Decky installs keep working unchanged.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use argh::FromArgs;
+
+use crate::steam::{
+    get_app_name, load_users_shortcuts, steamid64_to_steamid, steamid64_to_usteamid, LoginUser,
+};
+use crate::vendor::vdfr::AppInfo;
+
+#[derive(FromArgs)]
+/// Deck Screenshot Explorer backend.
+pub struct Args {
+    #[argh(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+pub enum Command {
+    Users(UsersCommand),
+    Shortcuts(ShortcutsCommand),
+    Apps(AppsCommand),
+    Export(ExportCommand),
+}
+
+#[derive(FromArgs)]
+/// List registered Steam users on this machine.
+#[argh(subcommand, name = "users")]
+pub struct UsersCommand {}
+
+#[derive(FromArgs)]
+/// List non-Steam shortcuts for a user.
+#[argh(subcommand, name = "shortcuts")]
+pub struct ShortcutsCommand {
+    #[argh(positional)]
+    /// steamid64 of the user
+    pub user: u64,
+}
+
+#[derive(FromArgs)]
+/// List resolved Steam app names from appinfo.vdf.
+#[argh(subcommand, name = "apps")]
+pub struct AppsCommand {}
+
+#[derive(FromArgs)]
+/// Copy a user's screenshots for one app out to a backup folder.
+#[argh(subcommand, name = "export")]
+pub struct ExportCommand {
+    #[argh(option)]
+    /// steamid64 of the user
+    pub user: u64,
+    #[argh(option)]
+    /// appid to export
+    pub app: u32,
+    #[argh(option)]
+    /// destination directory
+    pub out: PathBuf,
+}
+
+pub fn run_users(steam_users: &HashMap<u64, LoginUser>) {
+    for (id64, user) in steam_users {
+        let id3 = steamid64_to_usteamid(*id64);
+        let steamid = steamid64_to_steamid(*id64);
+        println!(
+            "{}\tsteamid64={}\tsteamid3={}\tsteamid={}",
+            user.username, id64, id3, steamid
+        );
+    }
+}
+
+pub fn run_shortcuts(user: u64) {
+    let uid3 = steamid64_to_usteamid(user);
+    let shortcuts = load_users_shortcuts(uid3);
+    for shortcut in shortcuts.values() {
+        println!("{}\t{}", shortcut.id, shortcut.name);
+    }
+}
+
+pub fn run_apps(app_info: &AppInfo) {
+    if let Err(e) = app_info.verify_all() {
+        eprintln!("warning: {}", e);
+    }
+
+    for app in app_info.apps.values() {
+        println!("{}\t{}", app.id, get_app_name(app));
+    }
+}
+
+pub async fn run_export(
+    user: u64,
+    app: u32,
+    out: &std::path::Path,
+    steam_root: &std::path::Path,
+) -> anyhow::Result<usize> {
+    let uid3 = steamid64_to_usteamid(user);
+    let screenshots_dir =
+        steam_root.join(format!("userdata/{}/760/remote/{}/screenshots", uid3, app));
+
+    if !screenshots_dir.is_dir() {
+        anyhow::bail!("No screenshots found for user {} app {}", user, app);
+    }
+
+    tokio::fs::create_dir_all(out).await?;
+
+    let mut entries = tokio::fs::read_dir(&screenshots_dir).await?;
+    let mut copied = 0usize;
+
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+
+        let ext_ok = entry
+            .path()
+            .extension()
+            .map(|e| ["jpg", "png", "webp"].contains(&e.to_string_lossy().as_ref()))
+            .unwrap_or(false);
+        if !ext_ok {
+            continue;
+        }
+
+        let dest = out.join(entry.file_name());
+        tokio::fs::copy(entry.path(), dest).await?;
+        copied += 1;
+    }
+
+    Ok(copied)
+}
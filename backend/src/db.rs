@@ -0,0 +1,344 @@
+//! Persistent SQLite index of screenshots.
+//!
+//! Scanning `userdata/<id>/760/remote/.../screenshots` on every request gets
+//! slow once a library has a few thousand captures, so we keep a small SQLite
+//! database alongside the plugin data and serve listing/search/pagination
+//! queries from it instead. The pool is intentionally tiny (r2d2 over a
+//! single SQLite file) since all access is local and mostly read-heavy.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::params;
+
+pub type DbPool = Arc<Pool<SqliteConnectionManager>>;
+
+/// A single indexed screenshot row.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ScreenshotRow {
+    pub steamid: u64,
+    pub appid: u32,
+    pub filename: String,
+    pub app_name: String,
+    pub captured_at: u64,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub file_size: u64,
+}
+
+/// Column to sort listing results by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortBy {
+    #[default]
+    Date,
+    Name,
+}
+
+/// Sort direction for listing results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Options used when listing screenshots for a user/app pair.
+#[derive(Debug, Default)]
+pub struct ListOptions {
+    pub page: usize,
+    pub per_page: usize,
+    pub sort_by: SortBy,
+    pub sort_order: SortOrder,
+    /// Inclusive `captured_at` lower bound (Unix epoch seconds).
+    pub from: Option<u64>,
+    /// Inclusive `captured_at` upper bound (Unix epoch seconds).
+    pub to: Option<u64>,
+}
+
+/// Parse a `sort` query value (`name`/`date`, optionally suffixed with
+/// `_asc`/`_desc`) into a `(SortBy, SortOrder)` pair, defaulting to
+/// `(Date, Desc)` for anything missing or unrecognized.
+pub fn parse_sort(sort: Option<&str>) -> (SortBy, SortOrder) {
+    let Some(sort) = sort else {
+        return (SortBy::default(), SortOrder::default());
+    };
+
+    let (field, order) = match sort.split_once('_') {
+        Some((field, order)) => (field, Some(order)),
+        None => (sort, None),
+    };
+
+    let sort_by = match field {
+        "name" => SortBy::Name,
+        _ => SortBy::Date,
+    };
+    let sort_order = match order {
+        Some("asc") => SortOrder::Asc,
+        Some("desc") => SortOrder::Desc,
+        _ => SortOrder::default(),
+    };
+
+    (sort_by, sort_order)
+}
+
+/// Open (creating if necessary) the index database and build a connection pool.
+pub fn open_pool(db_path: &Path) -> anyhow::Result<DbPool> {
+    if let Some(parent) = db_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let manager = SqliteConnectionManager::file(db_path);
+    let pool = Pool::builder().max_size(4).build(manager)?;
+
+    let conn = pool.get()?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS screenshots (
+            steamid     INTEGER NOT NULL,
+            appid       INTEGER NOT NULL,
+            filename    TEXT NOT NULL,
+            app_name    TEXT NOT NULL,
+            captured_at INTEGER NOT NULL,
+            width       INTEGER,
+            height      INTEGER,
+            file_size   INTEGER NOT NULL,
+            PRIMARY KEY (steamid, appid, filename)
+        );
+        CREATE INDEX IF NOT EXISTS idx_screenshots_user_app
+            ON screenshots (steamid, appid);",
+    )?;
+
+    Ok(Arc::new(pool))
+}
+
+/// Insert or refresh a single screenshot row.
+pub fn upsert_screenshot(pool: &DbPool, row: &ScreenshotRow) -> anyhow::Result<()> {
+    let conn = pool.get()?;
+    conn.execute(
+        "INSERT INTO screenshots (steamid, appid, filename, app_name, captured_at, width, height, file_size)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+         ON CONFLICT (steamid, appid, filename) DO UPDATE SET
+            app_name = excluded.app_name,
+            captured_at = excluded.captured_at,
+            width = excluded.width,
+            height = excluded.height,
+            file_size = excluded.file_size",
+        params![
+            row.steamid as i64,
+            row.appid,
+            row.filename,
+            row.app_name,
+            row.captured_at as i64,
+            row.width,
+            row.height,
+            row.file_size as i64,
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// List screenshots for a given user/app, sorted as `opts` asks, with an
+/// optional `captured_at` range filter and paging.
+pub fn list_screenshots(
+    pool: &DbPool,
+    steamid: u64,
+    appid: u32,
+    opts: &ListOptions,
+) -> anyhow::Result<(Vec<ScreenshotRow>, usize)> {
+    let conn = pool.get()?;
+
+    let from = opts.from.map(|v| v as i64);
+    let to = opts.to.map(|v| v as i64);
+
+    let total: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM screenshots
+         WHERE steamid = ?1 AND appid = ?2
+           AND (?3 IS NULL OR captured_at >= ?3) AND (?4 IS NULL OR captured_at <= ?4)",
+        params![steamid as i64, appid, from, to],
+        |row| row.get(0),
+    )?;
+
+    let order_column = match opts.sort_by {
+        SortBy::Date => "captured_at",
+        SortBy::Name => "filename",
+    };
+    let order_dir = match opts.sort_order {
+        SortOrder::Asc => "ASC",
+        SortOrder::Desc => "DESC",
+    };
+
+    let query = format!(
+        "SELECT steamid, appid, filename, app_name, captured_at, width, height, file_size
+         FROM screenshots
+         WHERE steamid = ?1 AND appid = ?2
+           AND (?3 IS NULL OR captured_at >= ?3) AND (?4 IS NULL OR captured_at <= ?4)
+         ORDER BY {order_column} {order_dir}
+         LIMIT ?5 OFFSET ?6"
+    );
+    let mut stmt = conn.prepare(&query)?;
+
+    let rows = stmt
+        .query_map(
+            params![
+                steamid as i64,
+                appid,
+                from,
+                to,
+                opts.per_page as i64,
+                (opts.page * opts.per_page) as i64,
+            ],
+            |row| {
+                Ok(ScreenshotRow {
+                    steamid: row.get::<_, i64>(0)? as u64,
+                    appid: row.get(1)?,
+                    filename: row.get(2)?,
+                    app_name: row.get(3)?,
+                    captured_at: row.get::<_, i64>(4)? as u64,
+                    width: row.get(5)?,
+                    height: row.get(6)?,
+                    file_size: row.get::<_, i64>(7)? as u64,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((rows, total as usize))
+}
+
+/// List every indexed filename for a given user/app, newest first, with no paging.
+///
+/// Used by the archive export endpoint to default to "everything" when the
+/// caller doesn't pass an explicit subset of filenames.
+pub fn list_all_filenames(pool: &DbPool, steamid: u64, appid: u32) -> anyhow::Result<Vec<String>> {
+    let conn = pool.get()?;
+
+    let mut stmt = conn.prepare(
+        "SELECT filename FROM screenshots
+         WHERE steamid = ?1 AND appid = ?2
+         ORDER BY captured_at DESC",
+    )?;
+
+    let filenames = stmt
+        .query_map(params![steamid as i64, appid], |row| row.get(0))?
+        .collect::<Result<Vec<String>, _>>()?;
+
+    Ok(filenames)
+}
+
+/// Decode just enough of `bytes` to read the image's pixel dimensions,
+/// without fully decoding it - this is all `scan_and_index` needs per file,
+/// and keeps the per-screenshot startup-scan cost low.
+fn decode_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    image::io::Reader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+/// Walk every known user's screenshot tree and (re)populate the index.
+///
+/// This is only meant to run once at startup; routes read from the DB
+/// afterwards instead of touching the filesystem directly. Directory
+/// listing and file reads go through `storage` rather than `tokio::fs`
+/// directly, so the scan also works against the S3 backend.
+pub async fn scan_and_index(
+    pool: &DbPool,
+    storage: &Arc<dyn crate::storage::StorageProvider>,
+    steam_root: &Path,
+    steam_users: &std::collections::HashMap<u64, crate::steam::LoginUser>,
+    users_shortcuts: &std::collections::HashMap<u64, std::collections::HashMap<u32, crate::steam::SteamShortcut>>,
+    app_info: &crate::vendor::vdfr::AppInfo,
+) -> anyhow::Result<usize> {
+    let mut indexed = 0usize;
+
+    for user_id in steam_users.keys() {
+        let uid3 = crate::steam::steamid64_to_usteamid(*user_id);
+        let remote_dir = steam_root.join(format!("userdata/{}/760/remote", uid3));
+
+        let app_dirs = match storage.list_dir(&remote_dir).await {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for app_dir in app_dirs {
+            if !app_dir.is_dir {
+                continue;
+            }
+
+            let Ok(appid) = app_dir.name.parse::<u32>() else {
+                continue;
+            };
+
+            let app_name = match app_info.apps.get(&appid) {
+                Some(app) => crate::steam::get_app_name(app),
+                None => match users_shortcuts.get(&uid3).and_then(|s| s.get(&appid)) {
+                    Some(shortcut) => shortcut.name.clone(),
+                    None => format!("Unknown App {}", appid),
+                },
+            };
+
+            let screenshots_dir = remote_dir.join(&app_dir.name).join("screenshots");
+            let files = match storage.list_dir(&screenshots_dir).await {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for file in files {
+                if file.is_dir {
+                    continue;
+                }
+
+                let filename = file.name;
+                let ext_ok = Path::new(&filename)
+                    .extension()
+                    .map(|e| ["jpg", "png", "webp"].contains(&e.to_string_lossy().as_ref()))
+                    .unwrap_or(false);
+                if !ext_ok {
+                    continue;
+                }
+
+                let file_path = screenshots_dir.join(&filename);
+                let Ok(meta) = storage.stat(&file_path).await else {
+                    continue;
+                };
+
+                // Prefer the capture time Steam encodes in the filename over
+                // mtime, which only reflects when the file was last touched
+                // on disk (e.g. after a sync or restore).
+                let captured_at = crate::metadata::parse_steam_filename_timestamp(&filename)
+                    .and_then(|(digits, _)| crate::metadata::steam_timestamp_to_epoch(&digits))
+                    .or_else(|| {
+                        meta.modified
+                            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                            .map(|d| d.as_secs())
+                    })
+                    .unwrap_or(0);
+
+                let dimensions = match crate::storage::read_all(storage.as_ref(), &file_path).await {
+                    Ok(bytes) => decode_dimensions(&bytes),
+                    Err(_) => None,
+                };
+
+                let row = ScreenshotRow {
+                    steamid: *user_id,
+                    appid,
+                    filename,
+                    app_name: app_name.clone(),
+                    captured_at,
+                    width: dimensions.map(|(w, _)| w),
+                    height: dimensions.map(|(_, h)| h),
+                    file_size: meta.size,
+                };
+
+                if upsert_screenshot(pool, &row).is_ok() {
+                    indexed += 1;
+                }
+            }
+        }
+    }
+
+    Ok(indexed)
+}
@@ -0,0 +1,53 @@
+//! Tracked background jobs for work dispatched off the request path.
+//!
+//! Resizing a large PNG thumbnail can take a while, so rather than just
+//! spawning the work and hoping, each generation is recorded as a `Job` that
+//! concurrent requests for the same source can coalesce onto, and that the
+//! frontend can poll progress on via `/jobs` instead of guessing.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Serialize;
+use tokio::sync::{Notify, RwLock};
+
+pub type JobId = String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    Running,
+    Done,
+    Failed,
+}
+
+/// A single tracked generation job. `notify` wakes everyone coalesced onto
+/// it once `state` moves out of `Running`.
+pub struct Job {
+    pub source: PathBuf,
+    pub state: JobState,
+    pub notify: Arc<Notify>,
+}
+
+pub type JobContainer = Arc<RwLock<HashMap<JobId, Job>>>;
+
+/// Job snapshot shaped for the `/jobs` response (no `Notify` handle).
+#[derive(Serialize)]
+pub struct JobSummary {
+    pub id: JobId,
+    pub source: PathBuf,
+    pub state: JobState,
+}
+
+pub async fn list_jobs(jobs: &JobContainer) -> Vec<JobSummary> {
+    jobs.read()
+        .await
+        .iter()
+        .map(|(id, job)| JobSummary {
+            id: id.clone(),
+            source: job.source.clone(),
+            state: job.state,
+        })
+        .collect()
+}
@@ -0,0 +1,180 @@
+//! Launches the system image viewer (or file manager) for a screenshot.
+//!
+//! This plugin usually runs inside a Decky/Flatpak sandbox, so a child
+//! process spawned with our inherited environment will often fail to start:
+//! it picks up `LD_LIBRARY_PATH`/`GST_PLUGIN_*` pointing at our bundled
+//! libraries, and `XDG_DATA_DIRS`/`PATH` get polluted with sandbox-only
+//! entries the host `.desktop` handler can't resolve. Before spawning we
+//! rebuild those pathlists and drop anything that looks sandbox-injected.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+
+/// Which sandbox (if any) this process appears to be running inside.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SandboxKind {
+    None,
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+pub fn detect_sandbox() -> SandboxKind {
+    if std::path::Path::new("/.flatpak-info").exists() || std::env::var_os("FLATPAK_ID").is_some()
+    {
+        SandboxKind::Flatpak
+    } else if std::env::var_os("SNAP").is_some() {
+        SandboxKind::Snap
+    } else if std::env::var_os("APPIMAGE").is_some() {
+        SandboxKind::AppImage
+    } else {
+        SandboxKind::None
+    }
+}
+
+/// De-duplicate a `:`-separated pathlist, keeping the first occurrence of
+/// each entry and dropping empty ones. Entries are not reordered, so host
+/// entries win over bundled ones only where they already come first.
+fn dedup_pathlist(value: &str) -> String {
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for entry in value.split(':') {
+        if entry.is_empty() {
+            continue;
+        }
+        if seen.insert(entry.to_string()) {
+            out.push(entry.to_string());
+        }
+    }
+    out.join(":")
+}
+
+/// Build a clean environment for the child process: normalized `PATH` and
+/// XDG pathlists, sandbox-injected library/plugin paths stripped, and empty
+/// values dropped entirely.
+pub fn build_child_env(sandbox: SandboxKind) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = std::env::vars().collect();
+
+    env.retain(|(key, value)| {
+        if value.is_empty() {
+            return false;
+        }
+        match sandbox {
+            SandboxKind::None => true,
+            _ => !(key == "LD_LIBRARY_PATH" || key.starts_with("GST_PLUGIN_")),
+        }
+    });
+
+    for (key, value) in env.iter_mut() {
+        if key == "PATH" || key == "XDG_DATA_DIRS" || key == "XDG_CONFIG_DIRS" {
+            *value = dedup_pathlist(value);
+        }
+    }
+
+    if !env.iter().any(|(k, _)| k == "XDG_DATA_DIRS") {
+        env.push((
+            "XDG_DATA_DIRS".to_string(),
+            "/usr/local/share:/usr/share".to_string(),
+        ));
+    }
+
+    env
+}
+
+/// Build a `Command` for `program`, routed through `flatpak-spawn --host`
+/// when running inside Flatpak so it queries/launches the *host's* MIME
+/// associations and binaries instead of the (usually MIME-association-less)
+/// sandbox runtime. Snap/AppImage processes already see the host's
+/// `xdg-mime`/`gtk-launch`/`xdg-open` directly, so they need no such escape.
+fn host_command(sandbox: SandboxKind, program: &str) -> tokio::process::Command {
+    match sandbox {
+        SandboxKind::Flatpak => {
+            let mut command = tokio::process::Command::new("flatpak-spawn");
+            command.arg("--host").arg(program);
+            command
+        }
+        _ => tokio::process::Command::new(program),
+    }
+}
+
+/// Resolve the `.desktop` handler registered for a file's MIME type via
+/// `xdg-mime query default`, falling back to `None` if nothing is registered
+/// or `xdg-mime` isn't available.
+pub async fn resolve_desktop_handler(sandbox: SandboxKind, path: &Path) -> Option<String> {
+    let mime = mime_guess::from_path(path).first_or_octet_stream().to_string();
+
+    let output = host_command(sandbox, "xdg-mime")
+        .args(["query", "default", &mime])
+        .output()
+        .await
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let desktop_file = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if desktop_file.is_empty() {
+        None
+    } else {
+        Some(desktop_file)
+    }
+}
+
+/// Launch the default image viewer for a file (like "Open").
+pub async fn open_file(path: &Path) -> anyhow::Result<()> {
+    spawn_xdg_open(path).await
+}
+
+/// Launch the default file manager at the containing folder (like "Reveal").
+pub async fn reveal_file(path: &Path) -> anyhow::Result<()> {
+    let parent = path
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("File has no parent directory"))?;
+    spawn_xdg_open(parent).await
+}
+
+async fn spawn_xdg_open(target: &Path) -> anyhow::Result<()> {
+    let sandbox = detect_sandbox();
+    let env = build_child_env(sandbox);
+    let desktop_handler = resolve_desktop_handler(sandbox, target).await;
+
+    tracing::info!(
+        "Launching {} for {:?} (sandbox: {:?})",
+        desktop_handler.as_deref().unwrap_or("xdg-open"),
+        target,
+        sandbox
+    );
+
+    // Prefer launching the resolved `.desktop` entry directly via
+    // `gtk-launch`; `xdg-open` is the fallback for when nothing is
+    // registered. Both go through the same `host_command` escape.
+    let mut command = match &desktop_handler {
+        Some(desktop_file) => {
+            let desktop_id = desktop_file.trim_end_matches(".desktop");
+            let mut command = host_command(sandbox, "gtk-launch");
+            command.arg(desktop_id).arg(target);
+            command
+        }
+        None => {
+            let mut command = host_command(sandbox, "xdg-open");
+            command.arg(target);
+            command
+        }
+    };
+
+    command
+        .env_clear()
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    let status = command.spawn()?.wait().await?;
+    if !status.success() {
+        anyhow::bail!("Launch handler exited with status {}", status);
+    }
+
+    Ok(())
+}
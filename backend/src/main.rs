@@ -1,5 +1,6 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
 
+use arc_swap::ArcSwap;
 use axum::{
     http::Uri,
     response::{Html, IntoResponse, Redirect},
@@ -18,20 +19,40 @@ use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 include!(concat!(env!("OUT_DIR"), "/index_html.rs"));
 
+mod archive;
+mod blurhash;
+mod cli;
+mod db;
+mod jobs;
+mod launcher;
+mod metadata;
 mod models;
 mod routes;
 mod steam;
+mod storage;
+mod thumbnail;
+mod tunnel;
+mod variant;
 mod vendor;
 
 #[derive(Clone)]
 pub struct SharedAppState {
-    pub app_info: Arc<vendor::vdfr::AppInfo>,
+    pub app_info: Arc<ArcSwap<vendor::vdfr::AppInfo>>,
     pub steam_users: Arc<HashMap<u64, LoginUser>>,
     pub users_shortcuts: Arc<HashMap<u64, HashMap<u32, SteamShortcut>>>,
+    pub db: db::DbPool,
+    pub tunnel: Arc<tunnel::TunnelManager>,
+    pub thumbnail_store: Arc<thumbnail::ThumbnailStore>,
+    pub storage: Arc<dyn storage::StorageProvider>,
 }
 
 #[tokio::main]
 async fn main() {
+    let cli_args: cli::Args = argh::from_env();
+    if let Some(command) = cli_args.command {
+        return run_cli(command).await;
+    }
+
     // fallback to current working directory
     let decky_log_path = std::env::var("DECKY_LOG_INTO").unwrap_or_else(|_| {
         std::env::current_dir()
@@ -62,7 +83,28 @@ async fn main() {
     let version = env!("CARGO_PKG_VERSION");
     tracing::info!("📸 Deck Screenshot Viewer v{}", version);
 
-    let steam_root = dunce::canonicalize(steam::get_steam_root_path()).unwrap();
+    let storage: Arc<dyn storage::StorageProvider> = match std::env::var("STORAGE_BACKEND").as_deref() {
+        Ok("s3") => {
+            let bucket = std::env::var("S3_BUCKET").unwrap_or_else(|_| {
+                tracing::error!("💥 STORAGE_BACKEND=s3 requires S3_BUCKET to be set");
+                std::process::exit(1);
+            });
+            tracing::info!("Using S3 storage backend (bucket: {})", bucket);
+            Arc::new(storage::S3Provider::from_env(bucket).await)
+        }
+        _ => Arc::new(storage::LocalProvider),
+    };
+
+    // Only `LocalProvider` requires the Deck's `userdata` tree to actually
+    // exist on this machine - `S3Provider` just echoes the configured root
+    // back as a key prefix, so a pure-S3 deployment never touches it.
+    let steam_root = storage
+        .validate_root(&steam::get_steam_root_path())
+        .await
+        .unwrap_or_else(|e| {
+            tracing::error!("💥 Steam root path is not accessible: {}", e);
+            std::process::exit(1);
+        });
     tracing::info!("Steam root path: {:?}", steam_root);
 
     let app_info_path = steam_root.join("appcache/appinfo.vdf");
@@ -83,10 +125,10 @@ async fn main() {
 
     drop(app_info_reader);
 
-    let app_info = Arc::new(app_info);
-    tracing::info!("Loaded {} apps", app_info.apps.len());
+    let app_info = Arc::new(ArcSwap::from_pointee(app_info));
+    tracing::info!("Loaded {} apps", app_info.load().apps.len());
     tracing::info!("Loading registered users...");
-    let steam_users = Arc::new(steam::get_steam_users(steam_root));
+    let steam_users = Arc::new(steam::get_steam_users(steam_root.clone()));
     tracing::info!("Loaded {} users", steam_users.len());
 
     // load shortcuts of each users
@@ -103,12 +145,59 @@ async fn main() {
         users_shortcuts.insert(uid3, shortcuts);
     }
 
+    tracing::info!("Opening screenshot index database...");
+    let db_path = match std::env::var("DECKY_PLUGIN_DIR") {
+        Ok(dir) => PathBuf::from(dir).join("screenshots.db"),
+        _ => PathBuf::from("screenshots.db"),
+    };
+    let db_pool = db::open_pool(&db_path).unwrap_or_else(|e| {
+        tracing::error!("💥 Failed to open screenshot index database: {}", e);
+        std::process::exit(1);
+    });
+
+    let thumbnails_dir = match std::env::var("DECKY_PLUGIN_DIR") {
+        Ok(dir) => PathBuf::from(dir).join("thumbnails"),
+        _ => PathBuf::from("thumbnails"),
+    };
+
+    let host_at = std::env::var("HOST").unwrap_or("127.0.0.1".to_string());
+    let port_at = std::env::var("PORT").unwrap_or("5158".to_string());
+
     let state = SharedAppState {
         app_info,
         steam_users,
         users_shortcuts: Arc::new(users_shortcuts),
+        db: db_pool.clone(),
+        tunnel: tunnel::TunnelManager::new(format!("{}:{}", host_at, port_at)),
+        thumbnail_store: Arc::new(thumbnail::ThumbnailStore::new(thumbnails_dir)),
+        storage,
     };
 
+    if std::env::var("TUNNEL").as_deref() == Ok("1") {
+        tracing::info!("TUNNEL=1 set, starting outbound tunnel...");
+        state.tunnel.start().await;
+    }
+
+    tracing::info!("Indexing screenshots (this only scans once at startup)...");
+    match db::scan_and_index(
+        &db_pool,
+        &state.storage,
+        &steam_root,
+        &state.steam_users,
+        &state.users_shortcuts,
+        &state.app_info.load(),
+    )
+    .await
+    {
+        Ok(count) => tracing::info!("Indexed {} screenshots", count),
+        Err(e) => tracing::error!("Failed to index screenshots: {}", e),
+    }
+
+    tokio::spawn(watch_appinfo_for_changes(
+        app_info_path.clone(),
+        state.app_info.clone(),
+    ));
+
     let decky_plugin_dir = std::env::var("DECKY_PLUGIN_DIR");
     tracing::info!("Decky plugin dir: {:?}", decky_plugin_dir);
     let assets_dir = match decky_plugin_dir {
@@ -116,6 +205,8 @@ async fn main() {
         _ => ServeDir::new("assets/assets"),
     };
 
+    let tunnel = state.tunnel.clone();
+
     let app: Router = Router::new()
         .route("/", get(index))
         .route(
@@ -131,9 +222,6 @@ async fn main() {
 
     let app = app.fallback(handle_404);
 
-    let host_at = std::env::var("HOST").unwrap_or("127.0.0.1".to_string());
-    let port_at = std::env::var("PORT").unwrap_or("5158".to_string());
-
     // run it
     let listener = TcpListener::bind(format!("{}:{}", host_at, port_at))
         .await
@@ -144,11 +232,41 @@ async fn main() {
         listener.local_addr().unwrap()
     );
     axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+        .with_graceful_shutdown(shutdown_signal(tunnel))
         .await
         .unwrap();
 }
 
+/// Run an offline one-shot command instead of starting the HTTP server.
+async fn run_cli(command: cli::Command) {
+    let steam_root = dunce::canonicalize(steam::get_steam_root_path()).unwrap();
+
+    match command {
+        cli::Command::Users(_) => {
+            let steam_users = steam::get_steam_users(steam_root);
+            cli::run_users(&steam_users);
+        }
+        cli::Command::Shortcuts(args) => {
+            cli::run_shortcuts(args.user);
+        }
+        cli::Command::Apps(_) => {
+            let app_info_path = steam_root.join("appcache/appinfo.vdf");
+            let data = std::fs::read(app_info_path).expect("failed to read appinfo.vdf");
+            let app_info = vendor::vdfr::AppInfo::load(&data).expect("failed to parse appinfo.vdf");
+            cli::run_apps(&app_info);
+        }
+        cli::Command::Export(args) => {
+            match cli::run_export(args.user, args.app, &args.out, &steam_root).await {
+                Ok(count) => println!("Exported {} screenshot(s) to {:?}", count, args.out),
+                Err(e) => {
+                    eprintln!("Export failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
 async fn handle_404(url: Uri) -> Redirect {
     let path = url.to_string();
     tracing::info!("404: {:?}", url);
@@ -161,7 +279,58 @@ async fn index() -> impl IntoResponse {
     Html(INDEX_HTML)
 }
 
-async fn shutdown_signal() {
+/// Periodically re-`stat`s `appcache/appinfo.vdf` and reloads it whenever the
+/// highest per-app `change_number` we've seen advances, so a long-running
+/// plugin session picks up renamed or newly-installed apps without a restart.
+async fn watch_appinfo_for_changes(path: PathBuf, app_info: Arc<ArcSwap<vendor::vdfr::AppInfo>>) {
+    let mut last_mtime = std::fs::metadata(&path).ok().and_then(|m| m.modified().ok());
+    let mut last_change_number = app_info.load().apps.values().map(|a| a.change_number).max().unwrap_or(0);
+
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+    loop {
+        interval.tick().await;
+
+        let mtime = match std::fs::metadata(&path).ok().and_then(|m| m.modified().ok()) {
+            Some(mtime) => mtime,
+            None => continue,
+        };
+
+        if Some(mtime) == last_mtime {
+            continue;
+        }
+        last_mtime = Some(mtime);
+
+        let data = match std::fs::read(&path) {
+            Ok(data) => data,
+            Err(e) => {
+                tracing::warn!("Failed to re-read appinfo.vdf: {}", e);
+                continue;
+            }
+        };
+
+        let reloaded = match vendor::vdfr::AppInfo::load(&data) {
+            Ok(reloaded) => reloaded,
+            Err(e) => {
+                tracing::warn!("Failed to reparse appinfo.vdf: {}", e);
+                continue;
+            }
+        };
+
+        let change_number = reloaded.apps.values().map(|a| a.change_number).max().unwrap_or(0);
+        if change_number > last_change_number {
+            tracing::info!(
+                "appinfo.vdf change number advanced ({} -> {}), reloading {} apps",
+                last_change_number,
+                change_number,
+                reloaded.apps.len()
+            );
+            last_change_number = change_number;
+            app_info.store(Arc::new(reloaded));
+        }
+    }
+}
+
+async fn shutdown_signal(tunnel: Arc<tunnel::TunnelManager>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -187,4 +356,6 @@ async fn shutdown_signal() {
             tracing::info!("Received SIGTERM, shutting down...");
         }
     }
+
+    tunnel.stop().await;
 }
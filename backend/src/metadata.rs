@@ -0,0 +1,167 @@
+//! Structured per-screenshot metadata: dimensions, file size, and a
+//! normalized capture timestamp pulled from either the Steam filename
+//! convention or embedded EXIF tags.
+
+use std::path::Path;
+
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Default)]
+pub struct ScreenshotDetails {
+    pub width: u32,
+    pub height: u32,
+    pub file_size: u64,
+    pub format: String,
+    /// Normalized ISO-8601 capture timestamp, if one could be determined.
+    pub captured_at: Option<String>,
+    /// Raw Steam filename timestamp (`YYYYMMDDHHMMSS`), if the filename matched.
+    pub filename_timestamp: Option<String>,
+    /// Raw EXIF `DateTimeOriginal` (or `DateTime`) tag, if present.
+    pub exif_timestamp: Option<String>,
+}
+
+/// Parse the `YYYYMMDDHHMMSS_n` prefix Steam uses for screenshot filenames
+/// into an ISO-8601 timestamp (UTC, since Steam doesn't record a timezone).
+pub fn parse_steam_filename_timestamp(filename: &str) -> Option<(String, String)> {
+    let stem = Path::new(filename).file_stem()?.to_str()?;
+    let digits = stem.split('_').next()?;
+
+    if digits.len() != 14 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let year = &digits[0..4];
+    let month = &digits[4..6];
+    let day = &digits[6..8];
+    let hour = &digits[8..10];
+    let minute = &digits[10..12];
+    let second = &digits[12..14];
+
+    let iso = format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        year, month, day, hour, minute, second
+    );
+
+    Some((digits.to_string(), iso))
+}
+
+/// Convert a `YYYYMMDDHHMMSS` digit string (as returned by
+/// `parse_steam_filename_timestamp`) into Unix epoch seconds (UTC).
+///
+/// Implements the civil-to-epoch conversion from Howard Hinnant's
+/// `days_from_civil` algorithm rather than pulling in a calendar crate for
+/// this one conversion.
+pub fn steam_timestamp_to_epoch(digits: &str) -> Option<u64> {
+    if digits.len() != 14 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let year: i64 = digits[0..4].parse().ok()?;
+    let month: i64 = digits[4..6].parse().ok()?;
+    let day: i64 = digits[6..8].parse().ok()?;
+    let hour: i64 = digits[8..10].parse().ok()?;
+    let minute: i64 = digits[10..12].parse().ok()?;
+    let second: i64 = digits[12..14].parse().ok()?;
+
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days_since_epoch = era * 146097 + doe - 719468;
+
+    let seconds = days_since_epoch * 86400 + hour * 3600 + minute * 60 + second;
+    u64::try_from(seconds).ok()
+}
+
+/// Extract `DateTimeOriginal` (falling back to `DateTime`) from a screenshot's
+/// EXIF data, if any. Returns the raw EXIF string (`YYYY:MM:DD HH:MM:SS`).
+pub fn read_exif_timestamp(source_bytes: &[u8]) -> Option<String> {
+    let mut reader = std::io::Cursor::new(source_bytes);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+
+    for tag in [exif::Tag::DateTimeOriginal, exif::Tag::DateTime] {
+        if let Some(field) = exif.get_field(tag, exif::In::PRIMARY) {
+            if let exif::Value::Ascii(ref values) = field.value {
+                if let Some(raw) = values.first() {
+                    return Some(String::from_utf8_lossy(raw).trim_end_matches('\0').to_string());
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn exif_to_iso(raw: &str) -> Option<String> {
+    // EXIF timestamps look like "YYYY:MM:DD HH:MM:SS".
+    let (date, time) = raw.split_once(' ')?;
+    let date = date.replace(':', "-");
+    Some(format!("{}T{}Z", date, time))
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_parse_steam_filename_timestamp() {
+        let (raw, iso) = super::parse_steam_filename_timestamp("20240131235959_1.jpg").unwrap();
+        assert_eq!(raw, "20240131235959");
+        assert_eq!(iso, "2024-01-31T23:59:59Z");
+    }
+
+    #[test]
+    fn test_parse_steam_filename_timestamp_rejects_non_steam_names() {
+        assert!(super::parse_steam_filename_timestamp("my_cool_screenshot.png").is_none());
+    }
+
+    #[test]
+    fn test_steam_timestamp_to_epoch() {
+        assert_eq!(
+            super::steam_timestamp_to_epoch("20240131235959"),
+            Some(1706745599)
+        );
+    }
+
+    #[test]
+    fn test_steam_timestamp_to_epoch_rejects_malformed_input() {
+        assert!(super::steam_timestamp_to_epoch("not-a-timestamp").is_none());
+    }
+}
+
+/// Build the full details payload for a screenshot, reading it and its
+/// size/mtime through `storage` so this also works against the S3 backend.
+pub async fn build_details(
+    storage: &dyn crate::storage::StorageProvider,
+    path: &Path,
+    filename: &str,
+) -> anyhow::Result<ScreenshotDetails> {
+    let file_size = storage.stat(path).await?.size;
+    let source_bytes = crate::storage::read_all(storage, path).await?;
+    let (width, height) = image::io::Reader::new(std::io::Cursor::new(&source_bytes))
+        .with_guessed_format()?
+        .into_dimensions()?;
+    let format = mime_guess::from_path(path).first_or_octet_stream().to_string();
+
+    let filename_match = parse_steam_filename_timestamp(filename);
+    let exif_timestamp = read_exif_timestamp(&source_bytes);
+
+    let captured_at = exif_timestamp
+        .as_deref()
+        .and_then(exif_to_iso)
+        .or_else(|| filename_match.as_ref().map(|(_, iso)| iso.clone()));
+
+    Ok(ScreenshotDetails {
+        width,
+        height,
+        file_size,
+        format,
+        captured_at,
+        filename_timestamp: filename_match.map(|(raw, _)| raw),
+        exif_timestamp,
+    })
+}
@@ -23,8 +23,32 @@ pub struct AppInfo {
     pub non_steam: bool,
 }
 
+/// Freshness metadata for a single appinfo.vdf entry, as surfaced by
+/// `/api/app/{id}/meta`.
+#[derive(Serialize, Debug)]
+pub struct AppMeta {
+    pub id: u32,
+    pub change_number: u32,
+    pub last_updated: u32,
+    pub pics_token: u64,
+    pub checksum_txt: String,
+}
+
 #[derive(Deserialize)]
 pub struct Pagination {
     pub page: Option<usize>,
     pub per_page: Option<usize>,
+    pub lang: Option<String>,
+    /// `name` or `date`, optionally suffixed with `_asc`/`_desc`
+    /// (e.g. `date_asc`). Defaults to `date_desc` (newest first).
+    pub sort: Option<String>,
+    /// Inclusive `captured_at` lower bound (Unix epoch seconds).
+    pub from: Option<u64>,
+    /// Inclusive `captured_at` upper bound (Unix epoch seconds).
+    pub to: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct LangQuery {
+    pub lang: Option<String>,
 }
@@ -5,21 +5,30 @@ use axum::{
     extract::{Path, Query, State},
     http::HeaderMap,
     response::IntoResponse,
-    routing::get,
-    Router,
+    routing::{get, post},
+    Json, Router,
 };
+use serde::Deserialize;
 use serde_json::json;
 use vdfr::Value;
 
 use crate::{
-    models::{AppInfo, Pagination, User},
+    models::{AppInfo, LangQuery, Pagination, User},
     steam::{
-        get_app_name, get_localized_app_name, get_steam_root_path, steamid64_to_steamid,
-        steamid64_to_usteamid,
+        get_app_name, get_localized_app_name, get_steam_root_path, resolve_localized_name,
+        steamid64_to_steamid, steamid64_to_usteamid,
     },
     SharedAppState,
 };
 
+/// Extract the `Accept-Language` header value, if present.
+fn accept_language_header(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
 pub async fn get_users(State(state): State<SharedAppState>) -> impl IntoResponse {
     let users = state
         .steam_users
@@ -45,7 +54,7 @@ pub async fn get_users(State(state): State<SharedAppState>) -> impl IntoResponse
     axum::Json(wrapped_json)
 }
 
-fn transform_vdfr_to_app(app: &vdfr::App) -> AppInfo {
+fn transform_vdfr_to_app(app: &vdfr::App, lang: Option<&str>, accept_language: Option<&str>) -> AppInfo {
     let app_name = get_app_name(app);
 
     let mut developers = Vec::new();
@@ -72,12 +81,12 @@ fn transform_vdfr_to_app(app: &vdfr::App) -> AppInfo {
     }
 
     let localized_name = get_localized_app_name(app);
-    // get "english" name or fallback to app name
-    let english_name = localized_name.get("english").unwrap_or(&app_name);
+    // resolve the best matching locale, falling back to the default app name
+    let display_name = resolve_localized_name(lang, accept_language, &localized_name, &app_name);
 
     AppInfo {
         id: app.id,
-        name: english_name.clone(),
+        name: display_name,
         localized_name: localized_name.clone(),
         developers: developers.clone(),
         publishers: publishers.clone(),
@@ -96,20 +105,16 @@ fn transform_shortcut_to_app(shortcut: &crate::steam::SteamShortcut) -> AppInfo
     }
 }
 
-async fn try_check_path_dir(path: &PathBuf, folder_name: &str) -> Result<bool, String> {
-    match tokio::fs::try_exists(path).await {
-        // Pass the data
-        Ok(exists) => Ok(exists),
-        // Pass the error
-        Err(io_error) => match io_error.kind() {
-            std::io::ErrorKind::NotFound => Ok(false),
-            other => {
-                let error_message = format!("Error checking {}: {}", folder_name, other);
-                tracing::error!("{}", &error_message);
-                Err(error_message)
-            }
-        },
-    }
+async fn try_check_path_dir(
+    storage: &dyn crate::storage::StorageProvider,
+    path: &PathBuf,
+    folder_name: &str,
+) -> Result<bool, String> {
+    storage.exists(path).await.map_err(|e| {
+        let error_message = format!("Error checking {}: {}", folder_name, e);
+        tracing::error!("{}", &error_message);
+        error_message
+    })
 }
 
 fn make_error(error: &str) -> String {
@@ -122,10 +127,45 @@ fn make_error(error: &str) -> String {
 
 pub async fn get_screenshot_apps(
     Path(id3): Path<u64>,
+    Query(lang_query): Query<LangQuery>,
+    request_headers: HeaderMap,
     State(state): State<SharedAppState>,
 ) -> impl IntoResponse {
-    let steam_folder = dunce::canonicalize(get_steam_root_path()).unwrap();
-    let user_folder = dunce::canonicalize(steam_folder.join(format!("userdata/{}", id3))).unwrap();
+    let steam_folder = match state.storage.validate_root(&get_steam_root_path()).await {
+        Ok(folder) => folder,
+        Err(e) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Content-Type", "application/json".parse().unwrap());
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                headers,
+                make_error(&e.to_string()),
+            );
+        }
+    };
+    let user_folder = match state
+        .storage
+        .resolve(&steam_folder, &PathBuf::from(format!("userdata/{}", id3)))
+        .await
+    {
+        Ok(folder) => folder,
+        Err(_) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Content-Type", "application/json".parse().unwrap());
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                serde_json::to_string(&json!({
+                    "ok": false,
+                    "error": "Invalid user id3 provided",
+                }))
+                .unwrap(),
+            );
+        }
+    };
+
+    let lang = lang_query.lang.clone();
+    let accept_language = accept_language_header(&request_headers);
 
     let mut headers = HeaderMap::new();
     headers.insert("Content-Type", "application/json".parse().unwrap());
@@ -134,20 +174,7 @@ pub async fn get_screenshot_apps(
     tracing::debug!("[get_screenshot_apps] steam folder: {:?}", steam_folder);
     tracing::debug!("[get_screenshot_apps] user folder: {:?}", user_folder);
 
-    // check if user_folder starts with steam_folder
-    if !user_folder.starts_with(&steam_folder) {
-        return (
-            axum::http::StatusCode::FORBIDDEN,
-            headers,
-            serde_json::to_string(&json!({
-                "ok": false,
-                "error": "Invalid user id3 provided",
-            }))
-            .unwrap(),
-        );
-    }
-
-    match try_check_path_dir(&user_folder, "User folder").await {
+    match try_check_path_dir(state.storage.as_ref(), &user_folder, "User folder").await {
         Ok(false) => {
             return (
                 axum::http::StatusCode::NOT_FOUND,
@@ -171,7 +198,7 @@ pub async fn get_screenshot_apps(
 
     let screenshot_apps = user_folder.join("760/remote");
 
-    match try_check_path_dir(&screenshot_apps, "Screenshot folder").await {
+    match try_check_path_dir(state.storage.as_ref(), &screenshot_apps, "Screenshot folder").await {
         Ok(false) => {
             return (
                 axum::http::StatusCode::OK,
@@ -277,9 +304,13 @@ pub async fn get_screenshot_apps(
                             ..Default::default()
                         })
                     } else {
-                        match state.app_info.apps.get(&app_id) {
+                        match state.app_info.load().apps.get(&app_id) {
                             Some(app) => {
-                                app_entries.push(transform_vdfr_to_app(app));
+                                app_entries.push(transform_vdfr_to_app(
+                                    app,
+                                    lang.as_deref(),
+                                    accept_language.as_deref(),
+                                ));
                             }
                             None => match shortcuts_data.get(&app_id) {
                                 Some(shortcut) => {
@@ -313,90 +344,50 @@ pub async fn get_screenshot_apps(
     )
 }
 
-async fn get_screenshot_folders(id3: u64, appid: u32) -> anyhow::Result<PathBuf> {
-    let steam_folder = dunce::canonicalize(get_steam_root_path()).unwrap();
-    let user_folder = dunce::canonicalize(steam_folder.join(format!("userdata/{}", id3)))?;
+async fn get_screenshot_folders(
+    storage: &dyn crate::storage::StorageProvider,
+    id3: u64,
+    appid: u32,
+) -> anyhow::Result<PathBuf> {
+    let steam_folder = storage.validate_root(&get_steam_root_path()).await?;
+    let user_folder = storage
+        .resolve(&steam_folder, &PathBuf::from(format!("userdata/{}", id3)))
+        .await
+        .map_err(|_| anyhow::anyhow!("Invalid user id3 provided"))?;
 
     tracing::debug!("[get_screenshot_folders] user ID3: {}", id3);
     tracing::debug!("[get_screenshot_folders] app ID: {}", appid);
     tracing::debug!("[get_screenshot_folders] steam folder: {:?}", steam_folder);
     tracing::debug!("[get_screenshot_folders] user folder: {:?}", user_folder);
 
-    // check if user_folder starts with steam_folder
-    if !user_folder.starts_with(&steam_folder) {
-        anyhow::bail!("Invalid user id3 provided");
-    }
-
-    match tokio::fs::try_exists(&user_folder).await {
-        Ok(exists) => {
-            if !exists {
-                anyhow::bail!("User folder not found");
-            }
-        }
-        Err(e) => match e.kind() {
-            std::io::ErrorKind::NotFound => {
-                anyhow::bail!("User folder not found");
-            }
-            other => {
-                anyhow::bail!("Failed to check if user folder exists: {}", other);
-            }
-        },
+    if !storage.exists(&user_folder).await? {
+        anyhow::bail!("User folder not found");
     }
 
     let base_folder = user_folder.join("760/remote");
     tracing::debug!("[get_screenshot_folders] base folder: {:?}", base_folder);
 
-    match tokio::fs::try_exists(&base_folder).await {
-        Ok(exists) => {
-            if !exists {
-                anyhow::bail!("Screenshot folder not found");
-            }
-        }
-        Err(e) => match e.kind() {
-            std::io::ErrorKind::NotFound => {
-                anyhow::bail!("Screenshot folder not found");
-            }
-            other => {
-                anyhow::bail!("Failed to check if screenshot folder exists: {}", other);
-            }
-        },
+    if !storage.exists(&base_folder).await? {
+        anyhow::bail!("Screenshot folder not found");
     }
 
-    let screenshots_folder =
-        dunce::canonicalize(base_folder.join(format!("{}/screenshots", appid)))?;
+    let screenshots_folder = storage
+        .resolve(&steam_folder, &PathBuf::from(format!("userdata/{}/760/remote/{}/screenshots", id3, appid)))
+        .await
+        .map_err(|_| anyhow::anyhow!("Invalid app ID provided"))?;
 
     tracing::debug!(
         "[get_screenshot_folders] screenshots folder: {:?}",
         screenshots_folder
     );
 
-    // check if screenshots_folder starts with steam_folder
-    if !screenshots_folder.starts_with(&steam_folder) {
-        anyhow::bail!("Invalid app ID provided");
-    }
-
-    // match tokio::fs::try_exists(&screenshots_folder).await {
-    //     Ok(exists) => {
-    //         if !exists {
-    //             anyhow::bail!("App screenshot folder not found");
-    //         }
-    //     }
-    //     Err(e) => match e.kind() {
-    //         std::io::ErrorKind::NotFound => {
-    //             anyhow::bail!("App screenshot folder not found");
-    //         }
-    //         other => {
-    //             anyhow::bail!("Failed to check if app screenshot folder exists: {}", other);
-    //         }
-    //     },
-    // }
-
     Ok(screenshots_folder)
 }
 
 pub async fn get_screenshot_app(
     Path((id3, appid)): Path<(u64, u32)>,
     Query(pagination): Query<Pagination>,
+    request_headers: HeaderMap,
     State(state): State<SharedAppState>,
 ) -> impl IntoResponse {
     let mut headers = HeaderMap::new();
@@ -404,6 +395,8 @@ pub async fn get_screenshot_app(
 
     let page = pagination.page.unwrap_or(0);
     let per_page = pagination.per_page.unwrap_or(10);
+    let lang = pagination.lang.clone();
+    let accept_language = accept_language_header(&request_headers);
 
     // check if per_page is not 10, 20, 50, 100
     if ![10, 20, 50, 100].contains(&per_page) {
@@ -418,7 +411,7 @@ pub async fn get_screenshot_app(
         );
     }
 
-    let screenshots_folder = match get_screenshot_folders(id3, appid).await {
+    let screenshots_folder = match get_screenshot_folders(state.storage.as_ref(), id3, appid).await {
         Ok(folder) => folder,
         Err(e) => {
             return (
@@ -435,7 +428,7 @@ pub async fn get_screenshot_app(
 
     let shortcuts_data = state.users_shortcuts.get(&id3).unwrap();
 
-    let app_info = match state.app_info.apps.get(&appid) {
+    let app_info = match state.app_info.load().apps.get(&appid) {
         Some(app) => {
             if app.id == 7 {
                 AppInfo {
@@ -444,7 +437,7 @@ pub async fn get_screenshot_app(
                     ..Default::default()
                 }
             } else {
-                transform_vdfr_to_app(app)
+                transform_vdfr_to_app(app, lang.as_deref(), accept_language.as_deref())
             }
         }
         None => {
@@ -482,7 +475,7 @@ pub async fn get_screenshot_app(
         },
     });
 
-    match try_check_path_dir(&screenshots_folder, "App screenshot folder").await {
+    match try_check_path_dir(state.storage.as_ref(), &screenshots_folder, "App screenshot folder").await {
         Ok(false) => {
             return (
                 axum::http::StatusCode::OK,
@@ -500,93 +493,43 @@ pub async fn get_screenshot_app(
         _ => (),
     }
 
-    // get all folders in the remote folder
-    let mut entries = match tokio::fs::read_dir(&screenshots_folder).await {
-        Ok(entries) => entries,
-        Err(io_error) => match io_error.kind() {
-            std::io::ErrorKind::NotFound => {
-                return (
-                    axum::http::StatusCode::OK,
-                    headers,
-                    serde_json::to_string(&default_err_data).unwrap(),
-                )
-            }
-            other => {
-                let error_msg = format!("Failed to read screenshot folder directory: {}", other);
+    let (sort_by, sort_order) = crate::db::parse_sort(pagination.sort.as_deref());
+
+    // Listing now comes from the SQLite index populated at startup instead of
+    // re-walking the screenshots folder on every request.
+    let list_opts = crate::db::ListOptions {
+        page,
+        per_page,
+        sort_by,
+        sort_order,
+        from: pagination.from,
+        to: pagination.to,
+    };
+
+    let (screenshot_rows, total_ss) =
+        match crate::db::list_screenshots(&state.db, id3, appid, &list_opts) {
+            Ok(result) => result,
+            Err(e) => {
                 return (
                     axum::http::StatusCode::INTERNAL_SERVER_ERROR,
                     headers,
-                    serde_json::to_string(&json!({
-                        "ok": false,
-                        "error": error_msg,
-                    }))
-                    .unwrap(),
+                    make_error(&format!("Failed to query screenshot index: {}", e)),
                 );
             }
-        },
-    };
-
-    let mut screenshot_data: Vec<PathBuf> = Vec::new();
-
-    loop {
-        let entry = match entries.next_entry().await {
-            Ok(Some(entry)) => entry,
-            Ok(None) => break, // No more entries
-            Err(io_error) => match io_error.kind() {
-                std::io::ErrorKind::NotFound => {
-                    return (
-                        axum::http::StatusCode::OK,
-                        headers,
-                        serde_json::to_string(&default_err_data).unwrap(),
-                    )
-                }
-                other => {
-                    let error_msg = format!(
-                        "Failed to get next entry for app screenshot folder: {}",
-                        other
-                    );
-                    return (
-                        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        headers,
-                        serde_json::to_string(&json!({
-                            "ok": false,
-                            "error": error_msg,
-                        }))
-                        .unwrap(),
-                    );
-                }
-            },
-        };
-
-        let file_type = match entry.file_type().await {
-            Ok(ft) => ft,
-            Err(_) => continue, // Ignore errors
         };
 
-        if file_type.is_file() {
-            match entry.path().extension() {
-                Some(file_ext) => {
-                    let ext_clean = file_ext.to_string_lossy().to_string();
-                    if ["jpg", "png", "webp"].contains(&ext_clean.as_str()) {
-                        screenshot_data.push(entry.path());
-                    }
-                }
-                _ => (),
-            }
-        }
+    let mut screenshot_entries = Vec::with_capacity(screenshot_rows.len());
+    for row in screenshot_rows {
+        let blurhash = crate::blurhash::get_or_compute(state.storage.as_ref(), &screenshots_folder.join(&row.filename))
+            .await
+            .ok();
+        screenshot_entries.push(json!({
+            "filename": row.filename,
+            "blurhash": blurhash,
+            "captured_at": row.captured_at,
+        }));
     }
 
-    // sort by filename
-    screenshot_data.sort_by(|a, b| a.file_stem().cmp(&b.file_stem()));
-    let total_ss = screenshot_data.len();
-    // take only the required page
-    let screenshot_files: Vec<String> = screenshot_data
-        .into_iter()
-        .skip(page * per_page)
-        .take(per_page)
-        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
-        .collect();
-
     (
         axum::http::StatusCode::OK,
         headers,
@@ -594,7 +537,7 @@ pub async fn get_screenshot_app(
             "ok": true,
             "data": {
                 "app": app_info,
-                "screenshots": screenshot_files,
+                "screenshots": screenshot_entries,
                 "pagination": {
                     "total": total_ss,
                     "page": page,
@@ -606,15 +549,249 @@ pub async fn get_screenshot_app(
     )
 }
 
+/// An inclusive byte range to serve, plus the total file size it was
+/// resolved against.
+struct ByteRange {
+    start: u64,
+    end: u64,
+    total: u64,
+}
+
+/// Parse a `Range: bytes=start-end` header against a known file size.
+/// Returns `Ok(None)` when there is no (or an unparseable/ignorable) range
+/// header, and `Err(())` when the range is syntactically a byte range but
+/// not satisfiable for this file size (caller should respond `416`).
+fn parse_range_header(range_header: Option<&str>, total: u64) -> Result<Option<ByteRange>, ()> {
+    let Some(range_header) = range_header else {
+        return Ok(None);
+    };
+
+    let Some(spec) = range_header.strip_prefix("bytes=") else {
+        return Ok(None);
+    };
+
+    // Only a single range is supported, matching what browsers send for media seeking.
+    let spec = spec.split(',').next().unwrap_or("").trim();
+    let Some((start_str, end_str)) = spec.split_once('-') else {
+        return Ok(None);
+    };
+
+    let (start, end) = if start_str.is_empty() {
+        // suffix range: "-N" means the last N bytes
+        let Ok(suffix_len) = end_str.parse::<u64>() else {
+            return Ok(None);
+        };
+        if suffix_len == 0 {
+            return Err(());
+        }
+        let start = total.saturating_sub(suffix_len);
+        (start, total.saturating_sub(1))
+    } else {
+        let Ok(start) = start_str.parse::<u64>() else {
+            return Ok(None);
+        };
+        let end = if end_str.is_empty() {
+            total.saturating_sub(1)
+        } else {
+            match end_str.parse::<u64>() {
+                Ok(end) => end,
+                Err(_) => return Ok(None),
+            }
+        };
+        (start, end)
+    };
+
+    if start > end || start >= total {
+        return Err(());
+    }
+
+    Ok(Some(ByteRange {
+        start,
+        end: end.min(total.saturating_sub(1)),
+        total,
+    }))
+}
+
+/// A strong ETag + Last-Modified pair derived from an object's size and mtime.
+fn file_cache_validators(meta: &crate::storage::ObjectMeta) -> (String, httpdate::HttpDate) {
+    let modified = meta.modified.unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+    let mtime_secs = modified
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let etag = format!("\"{:x}-{:x}\"", meta.size, mtime_secs);
+    (etag, httpdate::HttpDate::from(modified))
+}
+
+/// Screenshots and the thumbnails derived from them never change once
+/// written, so clients can cache them indefinitely.
+const IMAGE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// Validate `request_headers` against `meta`'s ETag/Last-Modified and either
+/// build the headers a `200` response should carry, or short-circuit with a
+/// `304 Not Modified`. `etag_suffix` lets callers that derive several bodies
+/// from one source file (e.g. thumbnail sizes/formats) keep a distinct ETag
+/// per variant; `vary` is forwarded onto a `Vary` header for callers whose
+/// body also depends on a request header (e.g. `Accept` for format
+/// negotiation). Shared by `get_screenshot_file` and
+/// `get_screenshot_file_thumbnail` so both honor the same cache contract.
+fn conditional_image_headers(
+    meta: &crate::storage::ObjectMeta,
+    request_headers: &HeaderMap,
+    etag_suffix: Option<&str>,
+    vary: Option<&'static str>,
+) -> Result<HeaderMap, axum::response::Response> {
+    let (etag, last_modified) = file_cache_validators(meta);
+    let etag = match etag_suffix {
+        Some(suffix) => format!("\"{}@{}\"", etag.trim_matches('"'), suffix),
+        None => etag,
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert("ETag", etag.parse().unwrap());
+    headers.insert("Last-Modified", last_modified.to_string().parse().unwrap());
+    headers.insert("Cache-Control", IMAGE_CACHE_CONTROL.parse().unwrap());
+    if let Some(vary) = vary {
+        headers.insert("Vary", vary.parse().unwrap());
+    }
+
+    if request_matches_cache(request_headers, &etag, &last_modified) {
+        return Err((axum::http::StatusCode::NOT_MODIFIED, headers, Body::empty()).into_response());
+    }
+
+    Ok(headers)
+}
+
+/// Whether a `Range` request should still be honored given an `If-Range`
+/// validator: absent `If-Range` always passes, otherwise the range is only
+/// honored if the validator still matches the current ETag/Last-Modified,
+/// so a file that changed between the initial load and a resumed range
+/// fetch falls back to a full `200` instead of splicing stale bytes in.
+fn if_range_satisfied(request_headers: &HeaderMap, etag: &str, last_modified: &httpdate::HttpDate) -> bool {
+    let if_range = match request_headers
+        .get(axum::http::header::IF_RANGE)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(value) => value,
+        None => return true,
+    };
+
+    if if_range.starts_with('"') || if_range.starts_with("W/\"") {
+        if_range == etag
+    } else {
+        if_range
+            .parse::<httpdate::HttpDate>()
+            .map(|date| date == *last_modified)
+            .unwrap_or(false)
+    }
+}
+
+fn request_matches_cache(request_headers: &HeaderMap, etag: &str, last_modified: &httpdate::HttpDate) -> bool {
+    if let Some(if_none_match) = request_headers
+        .get(axum::http::header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    {
+        if if_none_match.split(',').any(|tag| tag.trim() == etag) {
+            return true;
+        }
+    }
+
+    if let Some(if_modified_since) = request_headers
+        .get(axum::http::header::IF_MODIFIED_SINCE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<httpdate::HttpDate>().ok())
+    {
+        if if_modified_since >= *last_modified {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Serve a resized/re-encoded variant of a screenshot, generating and
+/// caching it on disk first if this is the first request for these params.
+async fn serve_screenshot_variant(
+    storage: &std::sync::Arc<dyn crate::storage::StorageProvider>,
+    source: &std::path::Path,
+    params: &crate::variant::VariantParams,
+) -> axum::response::Response {
+    let cache_path = crate::variant::variant_cache_path(source, params);
+
+    if let Ok(bytes) = tokio::fs::read(&cache_path).await {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            crate::variant::content_type_for(params).parse().unwrap(),
+        );
+        return (axum::http::StatusCode::OK, headers, bytes).into_response();
+    }
+
+    let source_bytes = match crate::storage::read_all(storage.as_ref(), source).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                text_headers,
+                format!("File not found: {}", e),
+            )
+                .into_response();
+        }
+    };
+    let params_owned = params.clone();
+    let bytes = match tokio::task::spawn_blocking(move || {
+        crate::variant::generate_variant(&source_bytes, &params_owned)
+    })
+    .await
+    {
+        Ok(Ok(bytes)) => bytes,
+        Ok(Err(e)) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                text_headers,
+                format!("Failed to generate variant: {}", e),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                text_headers,
+                format!("Variant generation task panicked: {}", e),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(parent) = cache_path.parent() {
+        let _ = tokio::fs::create_dir_all(parent).await;
+    }
+    let _ = tokio::fs::write(&cache_path, &bytes).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "Content-Type",
+        crate::variant::content_type_for(params).parse().unwrap(),
+    );
+    (axum::http::StatusCode::OK, headers, bytes).into_response()
+}
+
 pub async fn get_screenshot_file(
     Path((id3, appid, filename)): Path<(u64, u32, String)>,
+    Query(variant_params): Query<crate::variant::VariantParams>,
+    request_headers: HeaderMap,
+    State(state): State<SharedAppState>,
 ) -> axum::response::Response {
     let mut headers = HeaderMap::new();
     headers.insert("Content-Type", "application/json".parse().unwrap());
 
-    let steam_folders = dunce::canonicalize(get_steam_root_path()).unwrap();
-
-    let screenshots_folder = match get_screenshot_folders(id3, appid).await {
+    let screenshots_folder = match get_screenshot_folders(state.storage.as_ref(), id3, appid).await {
         Ok(folder) => folder,
         Err(e) => {
             return (
@@ -631,74 +808,216 @@ pub async fn get_screenshot_file(
     };
 
     // get file
-    let file_path = dunce::canonicalize(screenshots_folder.join(filename.clone())).unwrap();
-    if !file_path.starts_with(&steam_folders) {
-        return (
-            axum::http::StatusCode::FORBIDDEN,
-            headers,
-            serde_json::to_string(&json!({
-                "ok": false,
-                "error": "Invalid filename",
-            }))
-            .unwrap(),
-        )
-            .into_response();
+    let file_path = match state
+        .storage
+        .resolve(&screenshots_folder, &PathBuf::from(&filename))
+        .await
+    {
+        Ok(path) => path,
+        Err(_) => {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                serde_json::to_string(&json!({
+                    "ok": false,
+                    "error": "Invalid filename",
+                }))
+                .unwrap(),
+            )
+                .into_response();
+        }
+    };
+
+    if !variant_params.is_empty() {
+        return serve_screenshot_variant(&state.storage, &file_path, &variant_params).await;
     }
 
     let mimetype = mime_guess::from_path(&file_path)
         .first_or_octet_stream()
         .to_string();
 
-    let file_fs = match tokio::fs::File::open(file_path).await {
-        Ok(file) => file,
+    let meta = match state.storage.stat(&file_path).await {
+        Ok(meta) => meta,
         Err(error) => {
             let mut text_headers = HeaderMap::new();
             text_headers.insert("Content-Type", "text/plain".parse().unwrap());
-            match error.kind() {
-                std::io::ErrorKind::NotFound => {
-                    return (
-                        axum::http::StatusCode::NOT_FOUND,
-                        text_headers,
-                        "File not found".to_string(),
-                    )
-                        .into_response();
-                }
-                _ => {
-                    return (
-                        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        text_headers,
-                        format!("Failed to open file: {}", error),
-                    )
-                        .into_response();
-                }
-            }
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                text_headers,
+                format!("File not found: {}", error),
+            )
+                .into_response();
+        }
+    };
+
+    let (etag, last_modified) = file_cache_validators(&meta);
+    let mut file_headers = match conditional_image_headers(&meta, &request_headers, None, None) {
+        Ok(headers) => headers,
+        Err(not_modified) => return not_modified,
+    };
+
+    let total = meta.size;
+    let range_header = request_headers
+        .get(axum::http::header::RANGE)
+        .and_then(|v| v.to_str().ok());
+
+    let range = match parse_range_header(range_header, total) {
+        Ok(range) => range,
+        Err(()) => {
+            let mut range_headers = HeaderMap::new();
+            range_headers.insert("Content-Range", format!("bytes */{}", total).parse().unwrap());
+            return (
+                axum::http::StatusCode::RANGE_NOT_SATISFIABLE,
+                range_headers,
+                Body::empty(),
+            )
+                .into_response();
+        }
+    };
+    // A stale If-Range validator means the file changed since the client's
+    // last fetch: fall back to sending the whole thing instead of splicing
+    // a range of the old file into a range of the new one.
+    let range = if if_range_satisfied(&request_headers, &etag, &last_modified) {
+        range
+    } else {
+        None
+    };
+
+    file_headers.insert("Content-Type", mimetype.parse().unwrap());
+    file_headers.insert(
+        "Content-Disposition",
+        format!("inline; filename={}", filename).parse().unwrap(),
+    );
+    file_headers.insert("Accept-Ranges", "bytes".parse().unwrap());
+
+    let storage_range = range.as_ref().map(|r| (r.start, r.end));
+    let stream = match state.storage.open(&file_path, storage_range).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                HeaderMap::new(),
+                format!("Failed to open file: {}", error),
+            )
+                .into_response();
+        }
+    };
+
+    let status = match &range {
+        Some(range) => {
+            file_headers.insert(
+                "Content-Range",
+                format!("bytes {}-{}/{}", range.start, range.end, range.total)
+                    .parse()
+                    .unwrap(),
+            );
+            file_headers.insert(
+                "Content-Length",
+                (range.end - range.start + 1).to_string().parse().unwrap(),
+            );
+            axum::http::StatusCode::PARTIAL_CONTENT
         }
+        None => axum::http::StatusCode::OK,
     };
-    let stream = tokio_util::io::ReaderStream::new(file_fs);
+
     let body = Body::from_stream(stream);
 
-    let file_headers = {
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", mimetype.parse().unwrap());
-        headers.insert(
-            "Content-Disposition",
-            format!("inline; filename={}", filename).parse().unwrap(),
-        );
-        headers
+    (status, file_headers, body).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct ThumbnailQuery {
+    pub max_dimension: Option<u32>,
+    /// `small`/`medium`/`large`, a convenience alias for `max_dimension`.
+    pub size: Option<String>,
+}
+
+async fn generate_sized_thumbnail(
+    state: &SharedAppState,
+    screenshots_folder: &PathBuf,
+    id3: u64,
+    appid: u32,
+    filename: &str,
+    max_dimension: Option<u32>,
+    size_label: &str,
+    format: crate::thumbnail::OutputFormat,
+    request_headers: &HeaderMap,
+) -> axum::response::Response {
+    let stem = std::path::Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+
+    let source = match crate::thumbnail::find_source_screenshot(state.storage.as_ref(), screenshots_folder, &stem).await {
+        Some(source) => source,
+        None => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                text_headers,
+                "Thumbnail not found".to_string(),
+            )
+                .into_response();
+        }
     };
 
-    (axum::http::StatusCode::OK, file_headers, body).into_response()
+    // The source screenshot's mtime determines freshness of every size
+    // derived from it, so each size gets its own ETag off the same stat.
+    let meta = match state.storage.stat(&source).await {
+        Ok(meta) => meta,
+        Err(error) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                text_headers,
+                format!("File not found: {}", error),
+            )
+                .into_response();
+        }
+    };
+    let etag_suffix = format!("{}.{}", size_label, format.extension());
+    let mut file_headers = match conditional_image_headers(&meta, request_headers, Some(&etag_suffix), Some("Accept")) {
+        Ok(headers) => headers,
+        Err(not_modified) => return not_modified,
+    };
+
+    // Dispatched through the thumbnail store so concurrent requests for the
+    // same screenshot/size/format coalesce onto a single resize job instead
+    // of racing to generate and write the same cache file.
+    let image_bytes = match state
+        .thumbnail_store
+        .get_or_generate(&state.storage, id3, appid, filename, size_label, source, max_dimension, format)
+        .await
+    {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                text_headers,
+                format!("Failed to generate thumbnail: {}", e),
+            )
+                .into_response();
+        }
+    };
+
+    file_headers.insert("Content-Type", format.content_type().parse().unwrap());
+    (axum::http::StatusCode::OK, file_headers, image_bytes).into_response()
 }
 
 pub async fn get_screenshot_file_thumbnail(
     Path((id3, appid, filename)): Path<(u64, u32, String)>,
+    Query(query): Query<ThumbnailQuery>,
+    request_headers: HeaderMap,
+    State(state): State<SharedAppState>,
 ) -> axum::response::Response {
     let mut headers = HeaderMap::new();
     headers.insert("Content-Type", "application/json".parse().unwrap());
 
-    let steam_folders = dunce::canonicalize(get_steam_root_path()).unwrap();
-
-    let screenshots_folder = match get_screenshot_folders(id3, appid).await {
+    let screenshots_folder = match get_screenshot_folders(state.storage.as_ref(), id3, appid).await {
         Ok(folder) => folder,
         Err(e) => {
             return (
@@ -714,61 +1033,390 @@ pub async fn get_screenshot_file_thumbnail(
         }
     };
 
-    // get file and change to jpg
-    let file_path =
-        dunce::canonicalize(screenshots_folder.join(format!("thumbnails/{}", filename)))
-            .unwrap()
-            .with_extension("jpg");
-    if !file_path.starts_with(&steam_folders) {
-        return (
-            axum::http::StatusCode::FORBIDDEN,
-            headers,
-            serde_json::to_string(&json!({
-                "ok": false,
-                "error": "Invalid filename",
-            }))
-            .unwrap(),
+    let (max_dimension, size_label) =
+        crate::thumbnail::resolve_size(query.size.as_deref(), query.max_dimension);
+    let format = crate::thumbnail::negotiate_format(
+        request_headers
+            .get(axum::http::header::ACCEPT)
+            .and_then(|v| v.to_str().ok()),
+    );
+
+    // An explicit size, or a client asking for a modern format Steam's own
+    // pre-generated JPEG can't provide, always goes through the on-demand
+    // store instead of the passthrough below.
+    if size_label != "native" || format != crate::thumbnail::OutputFormat::Jpeg {
+        return generate_sized_thumbnail(
+            &state,
+            &screenshots_folder,
+            id3,
+            appid,
+            &filename,
+            max_dimension,
+            size_label,
+            format,
+            &request_headers,
         )
-            .into_response();
+        .await;
     }
 
-    let file_fs = match tokio::fs::File::open(file_path).await {
-        Ok(file) => file,
+    // Validate `filename` against the source screenshot rather than the
+    // (possibly not-yet-created) thumbnail cache file, since the thumbnail
+    // itself may not exist yet - that's exactly the "Ok(false)" case below.
+    let source_file = match state
+        .storage
+        .resolve(&screenshots_folder, &PathBuf::from(&filename))
+        .await
+    {
+        Ok(path) => path,
+        Err(_) => {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                serde_json::to_string(&json!({
+                    "ok": false,
+                    "error": "Invalid filename",
+                }))
+                .unwrap(),
+            )
+                .into_response();
+        }
+    };
+    let file_name = source_file.file_name().map(PathBuf::from).unwrap_or_else(|| PathBuf::from(&filename));
+    let file_path = screenshots_folder.join("thumbnails").join(file_name).with_extension("jpg");
+
+    let meta = match state.storage.exists(&file_path).await {
+        Ok(true) => match state.storage.stat(&file_path).await {
+            Ok(meta) => meta,
+            Err(error) => {
+                let mut text_headers = HeaderMap::new();
+                text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+                return (
+                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    text_headers,
+                    format!("Failed to check thumbnail: {}", error),
+                )
+                    .into_response();
+            }
+        },
+        Ok(false) => {
+            // No pre-generated Steam thumbnail: decode the original
+            // ourselves (no resize, since the caller didn't ask for one).
+            return generate_sized_thumbnail(
+                &state,
+                &screenshots_folder,
+                id3,
+                appid,
+                &filename,
+                max_dimension,
+                size_label,
+                format,
+                &request_headers,
+            )
+            .await;
+        }
+        Err(error) => {
+            let mut text_headers = HeaderMap::new();
+            text_headers.insert("Content-Type", "text/plain".parse().unwrap());
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                text_headers,
+                format!("Failed to check thumbnail: {}", error),
+            )
+                .into_response();
+        }
+    };
+
+    let mut file_headers = match conditional_image_headers(&meta, &request_headers, None, Some("Accept")) {
+        Ok(headers) => headers,
+        Err(not_modified) => return not_modified,
+    };
+
+    let stream = match state.storage.open(&file_path, None).await {
+        Ok(stream) => stream,
         Err(error) => {
             let mut text_headers = HeaderMap::new();
             text_headers.insert("Content-Type", "text/plain".parse().unwrap());
-            match error.kind() {
-                std::io::ErrorKind::NotFound => {
-                    return (
-                        axum::http::StatusCode::NOT_FOUND,
-                        text_headers,
-                        "Thumbnail not found".to_string(),
-                    )
-                        .into_response();
-                }
-                _ => {
-                    return (
-                        axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                        text_headers,
-                        format!("Failed to open thumbnail: {}", error),
-                    )
-                        .into_response();
-                }
-            }
+            return (
+                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                text_headers,
+                format!("Failed to open thumbnail: {}", error),
+            )
+                .into_response();
         }
     };
-    let stream = tokio_util::io::ReaderStream::new(file_fs);
     let body = Body::from_stream(stream);
 
-    let file_headers = {
-        let mut headers = HeaderMap::new();
-        headers.insert("Content-Type", "image/jpeg".parse().unwrap());
-        headers
-    };
+    file_headers.insert("Content-Type", "image/jpeg".parse().unwrap());
 
     (axum::http::StatusCode::OK, file_headers, body).into_response()
 }
 
+#[derive(Deserialize)]
+pub struct ScreenshotTarget {
+    pub id3: u64,
+    pub appid: u32,
+    pub filename: String,
+}
+
+async fn resolve_screenshot_path(
+    storage: &dyn crate::storage::StorageProvider,
+    target: &ScreenshotTarget,
+) -> Result<PathBuf, String> {
+    let screenshots_folder = get_screenshot_folders(storage, target.id3, target.appid)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    storage
+        .resolve(&screenshots_folder, &PathBuf::from(&target.filename))
+        .await
+        .map_err(|_| "Invalid filename".to_string())
+}
+
+pub async fn open_screenshot(
+    State(state): State<SharedAppState>,
+    Json(target): Json<ScreenshotTarget>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let file_path = match resolve_screenshot_path(state.storage.as_ref(), &target).await {
+        Ok(path) => path,
+        Err(e) => return (axum::http::StatusCode::FORBIDDEN, headers, make_error(&e)),
+    };
+
+    match crate::launcher::open_file(&file_path).await {
+        Ok(()) => (
+            axum::http::StatusCode::OK,
+            headers,
+            serde_json::to_string(&json!({ "ok": true })).unwrap(),
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            headers,
+            make_error(&e.to_string()),
+        ),
+    }
+}
+
+pub async fn reveal_screenshot(
+    State(state): State<SharedAppState>,
+    Json(target): Json<ScreenshotTarget>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let file_path = match resolve_screenshot_path(state.storage.as_ref(), &target).await {
+        Ok(path) => path,
+        Err(e) => return (axum::http::StatusCode::FORBIDDEN, headers, make_error(&e)),
+    };
+
+    match crate::launcher::reveal_file(&file_path).await {
+        Ok(()) => (
+            axum::http::StatusCode::OK,
+            headers,
+            serde_json::to_string(&json!({ "ok": true })).unwrap(),
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            headers,
+            make_error(&e.to_string()),
+        ),
+    }
+}
+
+pub async fn get_screenshot_details(
+    Path((id3, appid, filename)): Path<(u64, u32, String)>,
+    State(state): State<SharedAppState>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let screenshots_folder = match get_screenshot_folders(state.storage.as_ref(), id3, appid).await {
+        Ok(folder) => folder,
+        Err(e) => {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                serde_json::to_string(&json!({ "ok": false, "error": e.to_string() })).unwrap(),
+            );
+        }
+    };
+
+    let file_path = match state.storage.resolve(&screenshots_folder, &PathBuf::from(&filename)).await {
+        Ok(path) => path,
+        Err(_) => {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                make_error("Invalid filename"),
+            );
+        }
+    };
+
+    match crate::metadata::build_details(state.storage.as_ref(), &file_path, &filename).await {
+        Ok(details) => (
+            axum::http::StatusCode::OK,
+            headers,
+            serde_json::to_string(&json!({ "ok": true, "data": details })).unwrap(),
+        ),
+        Err(e) => (
+            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+            headers,
+            make_error(&format!("Failed to read screenshot details: {}", e)),
+        ),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    /// Comma-separated list of filenames to include. When omitted, every
+    /// indexed screenshot for the app is exported.
+    pub filenames: Option<String>,
+}
+
+pub async fn get_screenshot_archive(
+    Path((id3, appid)): Path<(u64, u32)>,
+    Query(query): Query<ArchiveQuery>,
+    State(state): State<SharedAppState>,
+) -> axum::response::Response {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let screenshots_folder = match get_screenshot_folders(state.storage.as_ref(), id3, appid).await {
+        Ok(folder) => folder,
+        Err(e) => {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                headers,
+                serde_json::to_string(&json!({ "ok": false, "error": e.to_string() })).unwrap(),
+            )
+                .into_response();
+        }
+    };
+
+    let filenames = match query.filenames {
+        Some(list) => list
+            .split(',')
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<_>>(),
+        None => match crate::db::list_all_filenames(&state.db, id3, appid) {
+            Ok(filenames) => filenames,
+            Err(e) => {
+                return (
+                    axum::http::StatusCode::INTERNAL_SERVER_ERROR,
+                    headers,
+                    make_error(&format!("Failed to query screenshot index: {}", e)),
+                )
+                    .into_response();
+            }
+        },
+    };
+
+    if filenames.is_empty() {
+        return (
+            axum::http::StatusCode::NOT_FOUND,
+            headers,
+            make_error("No screenshots to export"),
+        )
+            .into_response();
+    }
+
+    // Stream the archive straight into the response body instead of
+    // buffering it: a duplex pipe lets the writer task fill the ZIP while
+    // the reader half is handed to axum as the response stream.
+    let (reader, writer) = tokio::io::duplex(64 * 1024);
+    let storage = state.storage.clone();
+    tokio::spawn(async move {
+        if let Err(e) = crate::archive::write_zip_archive(writer, &storage, &screenshots_folder, &filenames).await {
+            tracing::error!("Failed to build screenshot archive: {}", e);
+        }
+    });
+
+    let mut archive_headers = HeaderMap::new();
+    archive_headers.insert("Content-Type", "application/zip".parse().unwrap());
+    archive_headers.insert(
+        "Content-Disposition",
+        format!("attachment; filename=\"{}-{}-screenshots.zip\"", id3, appid)
+            .parse()
+            .unwrap(),
+    );
+
+    let stream = tokio_util::io::ReaderStream::new(reader);
+    let body = Body::from_stream(stream);
+
+    (axum::http::StatusCode::OK, archive_headers, body).into_response()
+}
+
+pub async fn get_app_meta(
+    Path(appid): Path<u32>,
+    State(state): State<SharedAppState>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let app_info = state.app_info.load();
+    match app_info.apps.get(&appid) {
+        Some(app) => (
+            axum::http::StatusCode::OK,
+            headers,
+            serde_json::to_string(&json!({
+                "ok": true,
+                "data": crate::models::AppMeta {
+                    id: app.id,
+                    change_number: app.change_number,
+                    last_updated: app.last_update,
+                    pics_token: app.access_token,
+                    checksum_txt: hex::encode(app.checksum_txt),
+                },
+            }))
+            .unwrap(),
+        ),
+        None => (
+            axum::http::StatusCode::NOT_FOUND,
+            headers,
+            make_error("App not found"),
+        ),
+    }
+}
+
+pub async fn get_jobs(State(state): State<SharedAppState>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    let jobs = crate::jobs::list_jobs(&state.thumbnail_store.jobs()).await;
+
+    (
+        axum::http::StatusCode::OK,
+        headers,
+        serde_json::to_string(&json!({ "ok": true, "data": jobs })).unwrap(),
+    )
+}
+
+pub async fn start_tunnel(State(state): State<SharedAppState>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    state.tunnel.start().await;
+
+    (
+        axum::http::StatusCode::OK,
+        headers,
+        serde_json::to_string(&json!({ "ok": true, "data": state.tunnel.state() })).unwrap(),
+    )
+}
+
+pub async fn get_tunnel_status(State(state): State<SharedAppState>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Content-Type", "application/json".parse().unwrap());
+
+    (
+        axum::http::StatusCode::OK,
+        headers,
+        serde_json::to_string(&json!({ "ok": true, "data": state.tunnel.state() })).unwrap(),
+    )
+}
+
 pub fn api_routes(state: SharedAppState) -> Router<SharedAppState> {
     Router::new()
         .route("/users", get(get_users))
@@ -779,5 +1427,19 @@ pub fn api_routes(state: SharedAppState) -> Router<SharedAppState> {
             "/users/{id3}/{appid}/t/{filename}",
             get(get_screenshot_file_thumbnail),
         )
+        .route("/app/{appid}/meta", get(get_app_meta))
+        .route("/jobs", get(get_jobs))
+        .route("/screenshot/open", post(open_screenshot))
+        .route("/screenshot/reveal", post(reveal_screenshot))
+        .route("/tunnel/start", post(start_tunnel))
+        .route("/tunnel/status", get(get_tunnel_status))
+        .route(
+            "/users/{id3}/apps/{appid}/screenshots/{filename}/details",
+            get(get_screenshot_details),
+        )
+        .route(
+            "/users/{id3}/apps/{appid}/screenshots/archive",
+            get(get_screenshot_archive),
+        )
         .with_state(state)
 }
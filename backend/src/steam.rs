@@ -4,8 +4,6 @@
 
 use std::{collections::HashMap, path::PathBuf};
 
-use serde::Deserialize;
-
 const ID64_IDENT: u64 = 76561197960265728;
 
 pub fn get_steam_root_path() -> PathBuf {
@@ -21,13 +19,10 @@ pub fn get_steam_root_path() -> PathBuf {
 }
 
 /// A minimal representation of a Steam user.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug)]
 pub struct LoginUser {
-    #[serde(rename = "AccountName")]
     pub username: String,
-    #[serde(rename = "PersonaName")]
     pub display_name: String,
-    #[serde(rename = "Timestamp")]
     pub timestamp: u64,
 }
 
@@ -39,16 +34,45 @@ pub fn get_steam_users(root_path: PathBuf) -> HashMap<u64, LoginUser> {
         return HashMap::new();
     }
 
-    let mut login_users_reader = std::fs::File::open(login_users_path).unwrap();
-    let login_users: HashMap<String, LoginUser> =
-        keyvalues_serde::from_reader(&mut login_users_reader).unwrap();
+    let login_users_text = std::fs::read_to_string(login_users_path).unwrap();
+    let kv = match crate::vendor::vdfr::parse_text_keyvalues(&login_users_text) {
+        Ok(kv) => kv,
+        Err(_) => return HashMap::new(),
+    };
 
-    let transformed_users = login_users
-        .into_iter()
-        .map(|(k, v)| (k.parse().unwrap(), v))
-        .collect();
+    match kv.get("users") {
+        Some(crate::vendor::vdfr::Value::KeyValueType(users)) => users
+            .iter()
+            .filter_map(|(id64, user)| {
+                let crate::vendor::vdfr::Value::KeyValueType(user) = user else {
+                    return None;
+                };
 
-    transformed_users
+                let username = match user.get("AccountName") {
+                    Some(crate::vendor::vdfr::Value::StringType(v)) => v.clone(),
+                    _ => return None,
+                };
+                let display_name = match user.get("PersonaName") {
+                    Some(crate::vendor::vdfr::Value::StringType(v)) => v.clone(),
+                    _ => return None,
+                };
+                let timestamp = match user.get("Timestamp") {
+                    Some(crate::vendor::vdfr::Value::StringType(v)) => v.parse().ok()?,
+                    _ => return None,
+                };
+
+                Some((
+                    id64.parse().ok()?,
+                    LoginUser {
+                        username,
+                        display_name,
+                        timestamp,
+                    },
+                ))
+            })
+            .collect(),
+        _ => HashMap::new(),
+    }
 }
 
 /// A minimal representation of a Steam shortcut.
@@ -147,6 +171,111 @@ pub fn get_localized_app_name(app: &vdfr::App) -> HashMap<String, String> {
     names
 }
 
+/// Maps common IETF language tag prefixes (as sent in `Accept-Language`) to
+/// the locale names Steam uses as keys in `name_localized`.
+const LOCALE_ALIASES: &[(&str, &str)] = &[
+    ("en", "english"),
+    ("fr", "french"),
+    ("de", "german"),
+    ("es", "spanish"),
+    ("it", "italian"),
+    ("ja", "japanese"),
+    ("ko", "koreana"),
+    ("pt", "portuguese"),
+    ("ru", "russian"),
+    ("pl", "polish"),
+    ("nl", "dutch"),
+    ("sv", "swedish"),
+    ("tr", "turkish"),
+    ("uk", "ukrainian"),
+    ("vi", "vietnamese"),
+    ("th", "thai"),
+    ("zh-cn", "schinese"),
+    ("zh-hans", "schinese"),
+    ("zh-tw", "tchinese"),
+    ("zh-hant", "tchinese"),
+    ("zh", "schinese"),
+];
+
+/// Parse an `Accept-Language` header value into an ordered list of candidate
+/// language tags (lowercased, `q` weights ignored beyond ordering).
+fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut tags: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let part = part.trim();
+            if part.is_empty() {
+                return None;
+            }
+            let mut pieces = part.split(';');
+            let tag = pieces.next()?.trim().to_lowercase();
+            let q = pieces
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((tag, q))
+        })
+        .collect();
+
+    tags.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Resolve the best localized app name given an explicit `?lang=` override
+/// and/or an `Accept-Language` header, falling back to the default
+/// (English) name when nothing in the localized map matches.
+///
+/// Resolution order: `?lang=` as a Steam locale key or as an alias for one,
+/// then each `Accept-Language` candidate (exact match, then language-only
+/// match), then the `english` entry, then the provided default.
+pub fn resolve_localized_name(
+    lang_param: Option<&str>,
+    accept_language: Option<&str>,
+    localized: &HashMap<String, String>,
+    default_name: &str,
+) -> String {
+    if let Some(lang) = lang_param {
+        let lang = lang.to_lowercase();
+        if let Some(name) = localized.get(&lang) {
+            return name.clone();
+        }
+        if let Some((_, steam_locale)) = LOCALE_ALIASES.iter().find(|(alias, _)| *alias == lang) {
+            if let Some(name) = localized.get(*steam_locale) {
+                return name.clone();
+            }
+        }
+    }
+
+    if let Some(header) = accept_language {
+        for tag in parse_accept_language(header) {
+            if let Some(name) = localized.get(&tag) {
+                return name.clone();
+            }
+            if let Some((_, steam_locale)) = LOCALE_ALIASES.iter().find(|(alias, _)| *alias == tag) {
+                if let Some(name) = localized.get(*steam_locale) {
+                    return name.clone();
+                }
+            }
+
+            // language-only match, e.g. "en-us" -> "en"
+            if let Some(primary) = tag.split('-').next() {
+                if let Some((_, steam_locale)) =
+                    LOCALE_ALIASES.iter().find(|(alias, _)| *alias == primary)
+                {
+                    if let Some(name) = localized.get(*steam_locale) {
+                        return name.clone();
+                    }
+                }
+            }
+        }
+    }
+
+    localized
+        .get("english")
+        .cloned()
+        .unwrap_or_else(|| default_name.to_string())
+}
+
 pub fn steamid64_to_steamid(steamid64: u64) -> u64 {
     let acct = steamid64 - ID64_IDENT;
     acct / 2
@@ -162,8 +291,36 @@ pub fn clamp_i32_to_u24(value: i32) -> u32 {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     #[test]
     fn test_clamp_works() {
         assert_eq!(super::clamp_i32_to_u24(-1195449660), 12509892);
     }
+
+    #[test]
+    fn test_resolve_localized_name_prefers_lang_param() {
+        let mut localized = HashMap::new();
+        localized.insert("english".to_string(), "Half-Life".to_string());
+        localized.insert("french".to_string(), "Half-Life (fr)".to_string());
+
+        let name = super::resolve_localized_name(Some("fr"), None, &localized, "Half-Life");
+        assert_eq!(name, "Half-Life (fr)");
+    }
+
+    #[test]
+    fn test_resolve_localized_name_falls_back_to_accept_language() {
+        let mut localized = HashMap::new();
+        localized.insert("german".to_string(), "Half-Life (de)".to_string());
+
+        let name = super::resolve_localized_name(None, Some("de-DE,de;q=0.9"), &localized, "Half-Life");
+        assert_eq!(name, "Half-Life (de)");
+    }
+
+    #[test]
+    fn test_resolve_localized_name_falls_back_to_default() {
+        let localized = HashMap::new();
+        let name = super::resolve_localized_name(Some("xx"), Some("xx-XX"), &localized, "Half-Life");
+        assert_eq!(name, "Half-Life");
+    }
 }
@@ -0,0 +1,370 @@
+//! Pluggable storage backend for reading screenshot bytes.
+//!
+//! The screenshot/thumbnail routes used to read straight off the local
+//! filesystem via `tokio::fs`, which meant the Deck's `userdata` directory
+//! had to be mounted wherever the server ran. Routing reads through
+//! `StorageProvider` instead lets the explorer be pointed at an
+//! S3-compatible bucket via `STORAGE_BACKEND=s3` without touching the route
+//! handlers. Locally-generated derivatives (thumbnail/variant caches) are
+//! left on local disk regardless of backend - they're a server-side
+//! optimization, not part of the user's screenshot library.
+
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use futures_util::{Stream, StreamExt};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+pub type ByteStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+
+/// Size and modification time for a stored object, used to build
+/// `ETag`/`Last-Modified` cache validators without reading the body.
+pub struct ObjectMeta {
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+/// One immediate child of a `list_dir` path.
+pub struct DirEntry {
+    pub name: String,
+    pub is_dir: bool,
+}
+
+#[async_trait::async_trait]
+pub trait StorageProvider: Send + Sync {
+    /// Resolve `relative` against `base`, rejecting anything that would
+    /// escape it (e.g. via `..`) - the same safety check every route used
+    /// to do by hand with `dunce::canonicalize` + `starts_with` against the
+    /// local filesystem. Routing it through here instead means that check
+    /// also works for backends (like S3) that don't have a local filesystem
+    /// to canonicalize against.
+    async fn resolve(&self, base: &Path, relative: &Path) -> anyhow::Result<PathBuf>;
+
+    /// Validate `root` (the Steam root directory) as a base path for
+    /// `resolve`. `LocalProvider` requires it to exist on disk and
+    /// canonicalizes it; `S3Provider` has no local filesystem to check it
+    /// against, so it's returned unchanged - S3 never needs the Deck's
+    /// `userdata` tree mounted on the server.
+    async fn validate_root(&self, root: &Path) -> anyhow::Result<PathBuf>;
+
+    /// Whether `path` exists in this backend.
+    async fn exists(&self, path: &Path) -> anyhow::Result<bool>;
+
+    /// Size/mtime for `path`, without reading its body.
+    async fn stat(&self, path: &Path) -> anyhow::Result<ObjectMeta>;
+
+    /// Open `path` for reading, optionally restricted to an inclusive
+    /// `(start, end)` byte range (mirrors an HTTP `Range` request, and maps
+    /// directly onto an S3 `GetObject` range).
+    async fn open(&self, path: &Path, range: Option<(u64, u64)>) -> anyhow::Result<ByteStream>;
+
+    /// Write `bytes` to `path`, creating any missing parent directories.
+    async fn put(&self, path: &Path, bytes: &[u8]) -> anyhow::Result<()>;
+
+    /// List the immediate children of `path` (non-recursive). Used by the
+    /// startup scan to discover per-user app folders and their screenshots
+    /// without walking the local filesystem directly, so it also works
+    /// against a bucket with no real directory structure.
+    async fn list_dir(&self, path: &Path) -> anyhow::Result<Vec<DirEntry>>;
+}
+
+/// Reads/writes directly against the local filesystem - the default, and
+/// what every route did before `StorageProvider` existed.
+pub struct LocalProvider;
+
+#[async_trait::async_trait]
+impl StorageProvider for LocalProvider {
+    async fn resolve(&self, base: &Path, relative: &Path) -> anyhow::Result<PathBuf> {
+        let resolved = tokio::fs::canonicalize(base.join(relative)).await?;
+        if !resolved.starts_with(base) {
+            anyhow::bail!("path escapes base directory");
+        }
+        Ok(resolved)
+    }
+
+    async fn validate_root(&self, root: &Path) -> anyhow::Result<PathBuf> {
+        Ok(tokio::fs::canonicalize(root).await?)
+    }
+
+    async fn exists(&self, path: &Path) -> anyhow::Result<bool> {
+        Ok(tokio::fs::try_exists(path).await?)
+    }
+
+    async fn stat(&self, path: &Path) -> anyhow::Result<ObjectMeta> {
+        let metadata = tokio::fs::metadata(path).await?;
+        Ok(ObjectMeta {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    async fn open(&self, path: &Path, range: Option<(u64, u64)>) -> anyhow::Result<ByteStream> {
+        let mut file = tokio::fs::File::open(path).await?;
+
+        if let Some((start, _end)) = range {
+            file.seek(std::io::SeekFrom::Start(start)).await?;
+        }
+
+        let stream: ByteStream = match range {
+            Some((start, end)) => Box::pin(tokio_util::io::ReaderStream::new(file.take(end - start + 1))),
+            None => Box::pin(tokio_util::io::ReaderStream::new(file)),
+        };
+
+        Ok(stream)
+    }
+
+    async fn put(&self, path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, bytes).await?;
+        Ok(())
+    }
+
+    async fn list_dir(&self, path: &Path) -> anyhow::Result<Vec<DirEntry>> {
+        let mut read_dir = tokio::fs::read_dir(path).await?;
+        let mut entries = Vec::new();
+        while let Some(entry) = read_dir.next_entry().await? {
+            let is_dir = entry.file_type().await?.is_dir();
+            entries.push(DirEntry {
+                name: entry.file_name().to_string_lossy().to_string(),
+                is_dir,
+            });
+        }
+        Ok(entries)
+    }
+}
+
+/// Reads/writes screenshots from an S3-compatible bucket instead of the
+/// local filesystem, so the explorer can run without the Deck's `userdata`
+/// directory mounted on the server. Configured via `S3_BUCKET` (and the
+/// usual `AWS_*`/endpoint environment variables picked up by the SDK).
+pub struct S3Provider {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+}
+
+impl S3Provider {
+    pub async fn from_env(bucket: String) -> Self {
+        let config = aws_config::load_from_env().await;
+        let client = aws_sdk_s3::Client::new(&config);
+        Self { client, bucket }
+    }
+
+    fn key_for(&self, path: &Path) -> String {
+        path.to_string_lossy().replace('\\', "/")
+    }
+}
+
+#[async_trait::async_trait]
+impl StorageProvider for S3Provider {
+    // There's no real filesystem here to canonicalize against (and no
+    // symlinks to resolve), so `relative` is normalized lexically instead,
+    // rejecting any `..` that would climb back out of `base`.
+    async fn resolve(&self, base: &Path, relative: &Path) -> anyhow::Result<PathBuf> {
+        resolve_lexical(base, relative)
+    }
+
+    // No local filesystem to canonicalize against, and no on-disk mount to
+    // require - `root` is already just a key prefix for this backend.
+    async fn validate_root(&self, root: &Path) -> anyhow::Result<PathBuf> {
+        Ok(root.to_path_buf())
+    }
+
+    async fn exists(&self, path: &Path) -> anyhow::Result<bool> {
+        match self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(self.key_for(path))
+            .send()
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(aws_sdk_s3::error::SdkError::ServiceError(e)) if e.err().is_not_found() => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn stat(&self, path: &Path) -> anyhow::Result<ObjectMeta> {
+        let output = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(self.key_for(path))
+            .send()
+            .await?;
+
+        Ok(ObjectMeta {
+            size: output.content_length().unwrap_or(0) as u64,
+            modified: output
+                .last_modified()
+                .and_then(|dt| SystemTime::try_from(*dt).ok()),
+        })
+    }
+
+    async fn open(&self, path: &Path, range: Option<(u64, u64)>) -> anyhow::Result<ByteStream> {
+        let mut request = self.client.get_object().bucket(&self.bucket).key(self.key_for(path));
+        if let Some((start, end)) = range {
+            request = request.range(format!("bytes={}-{}", start, end));
+        }
+
+        let output = request.send().await?;
+        let stream = output
+            .body
+            .map(|chunk| chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)));
+
+        Ok(Box::pin(stream))
+    }
+
+    async fn put(&self, path: &Path, bytes: &[u8]) -> anyhow::Result<()> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.key_for(path))
+            .body(bytes.to_vec().into())
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    // S3 has no real directories, so "listing" `path` means querying for
+    // objects under it as a `/`-delimited prefix: `CommonPrefixes` are the
+    // pseudo-subdirectories, `Contents` are the actual objects directly in it.
+    async fn list_dir(&self, path: &Path) -> anyhow::Result<Vec<DirEntry>> {
+        let mut prefix = self.key_for(path);
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+
+        let mut entries = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut request = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&prefix)
+                .delimiter("/");
+            if let Some(token) = continuation_token {
+                request = request.continuation_token(token);
+            }
+
+            let output = request.send().await?;
+
+            for common_prefix in output.common_prefixes() {
+                if let Some(name) = common_prefix
+                    .prefix()
+                    .and_then(|p| p.strip_prefix(&prefix))
+                    .map(|name| name.trim_end_matches('/'))
+                    .filter(|name| !name.is_empty())
+                {
+                    entries.push(DirEntry {
+                        name: name.to_string(),
+                        is_dir: true,
+                    });
+                }
+            }
+
+            for object in output.contents() {
+                if let Some(name) = object.key().and_then(|k| k.strip_prefix(&prefix)).filter(|name| !name.is_empty()) {
+                    entries.push(DirEntry {
+                        name: name.to_string(),
+                        is_dir: false,
+                    });
+                }
+            }
+
+            continuation_token = output.next_continuation_token().map(|s| s.to_string());
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Lexically join `relative` onto `base`, rejecting any `..` that climbs back
+/// out of `base` or any absolute path component - the traversal check behind
+/// `S3Provider::resolve`, pulled out as a free function since it has no
+/// dependency on the S3 client and is otherwise untestable without one.
+fn resolve_lexical(base: &Path, relative: &Path) -> anyhow::Result<PathBuf> {
+    let mut resolved = base.to_path_buf();
+    for component in relative.components() {
+        match component {
+            std::path::Component::Normal(part) => resolved.push(part),
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if resolved == base {
+                    anyhow::bail!("path escapes base directory");
+                }
+                resolved.pop();
+            }
+            std::path::Component::RootDir | std::path::Component::Prefix(_) => {
+                anyhow::bail!("absolute paths are not allowed");
+            }
+        }
+    }
+
+    if !resolved.starts_with(base) {
+        anyhow::bail!("path escapes base directory");
+    }
+    Ok(resolved)
+}
+
+/// Read the entirety of `path` from `storage` into memory. For callers like
+/// thumbnail/variant generation that decode the whole image at once (rather
+/// than streaming it straight to the response body), this is the only way
+/// to reach the source bytes that also works against the S3 backend.
+pub async fn read_all(storage: &dyn StorageProvider, path: &Path) -> anyhow::Result<Vec<u8>> {
+    let stream = storage.open(path, None).await?;
+    let mut reader = tokio_util::io::StreamReader::new(stream);
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_local_resolve_rejects_path_traversal() {
+        let root = std::env::temp_dir().join(format!("deck_screenshot_storage_test_{}", std::process::id()));
+        let base = root.join("base");
+        std::fs::create_dir_all(&base).unwrap();
+        std::fs::write(root.join("secret.txt"), b"secret").unwrap();
+        std::fs::write(base.join("safe.txt"), b"ok").unwrap();
+
+        let provider = LocalProvider;
+        let base = provider.validate_root(&base).await.unwrap();
+
+        let escaped = provider.resolve(&base, Path::new("../secret.txt")).await;
+        assert!(escaped.is_err(), "\"../secret.txt\" should not resolve outside base");
+
+        let escaped_deep = provider.resolve(&base, Path::new("../../../../etc/passwd")).await;
+        assert!(escaped_deep.is_err(), "a deep \"../..\" traversal should not resolve outside base");
+
+        let allowed = provider.resolve(&base, Path::new("safe.txt")).await;
+        assert!(allowed.is_ok(), "a plain filename within base should still resolve");
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_s3_resolve_lexical_rejects_path_traversal() {
+        let base = Path::new("userdata/1/760/remote/220/screenshots");
+
+        let escaped = resolve_lexical(base, Path::new("../../etc/passwd"));
+        assert!(escaped.is_err(), "\"../../etc/passwd\" should not resolve outside base");
+
+        let escaped_absolute = resolve_lexical(base, Path::new("/etc/passwd"));
+        assert!(escaped_absolute.is_err(), "an absolute path should be rejected outright");
+
+        let allowed = resolve_lexical(base, Path::new("20240131235959_1.jpg"));
+        assert_eq!(allowed.unwrap(), base.join("20240131235959_1.jpg"));
+    }
+}
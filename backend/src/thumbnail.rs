@@ -0,0 +1,278 @@
+//! On-the-fly thumbnail generation.
+//!
+//! Steam writes a JPEG thumbnail alongside every screenshot it captures, but
+//! non-Steam shortcuts and externally imported screenshots often have none.
+//! When the pre-generated thumbnail is missing, or a specific size or format
+//! is requested, we decode the original image ourselves, downscale it to the
+//! requested size (native resolution when none is given), and cache the
+//! result under the `ThumbnailStore` directory so later requests skip the
+//! regeneration.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use image::imageops::FilterType;
+use tokio::sync::{Notify, RwLock};
+
+use crate::jobs::{Job, JobContainer, JobState};
+use crate::storage::StorageProvider;
+
+/// Bounding dimension for the `small` named size.
+pub const SIZE_SMALL: u32 = 128;
+/// Bounding dimension for the `medium` named size.
+pub const SIZE_MEDIUM: u32 = 256;
+/// Bounding dimension for the `large` named size.
+pub const SIZE_LARGE: u32 = 512;
+
+/// Resolve a `size`/`max_dimension` query pair into the bounding dimension to
+/// generate at (`None` meaning native resolution, no resize) plus a stable
+/// cache-key label for it.
+///
+/// A `size` name takes priority; an arbitrary `max_dimension` is snapped up
+/// to the smallest preset bucket that covers it (anything above
+/// `SIZE_MEDIUM` becomes `large`) so requests can't multiply the number of
+/// cached variants per screenshot. Omitting both yields `(None, "native")`.
+pub fn resolve_size(size: Option<&str>, max_dimension: Option<u32>) -> (Option<u32>, &'static str) {
+    if let Some(size) = size {
+        return match size {
+            "small" => (Some(SIZE_SMALL), "small"),
+            "medium" => (Some(SIZE_MEDIUM), "medium"),
+            "large" => (Some(SIZE_LARGE), "large"),
+            _ => (None, "native"),
+        };
+    }
+
+    match max_dimension {
+        Some(dim) if dim <= SIZE_SMALL => (Some(SIZE_SMALL), "small"),
+        Some(dim) if dim <= SIZE_MEDIUM => (Some(SIZE_MEDIUM), "medium"),
+        Some(_) => (Some(SIZE_LARGE), "large"),
+        None => (None, "native"),
+    }
+}
+
+/// Locate the original screenshot a thumbnail filename refers to, by
+/// trying `stem` with each known screenshot extension in turn inside
+/// `screenshots_folder`.
+pub async fn find_source_screenshot(
+    storage: &dyn StorageProvider,
+    screenshots_folder: &Path,
+    stem: &str,
+) -> Option<PathBuf> {
+    for ext in ["jpg", "png", "webp"] {
+        let candidate = screenshots_folder.join(format!("{}.{}", stem, ext));
+        if storage.exists(&candidate).await.unwrap_or(false) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Thumbnail encoding, chosen per-request by negotiating the client's
+/// `Accept` header against what the `image` crate can encode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Avif,
+    WebP,
+    Jpeg,
+}
+
+impl OutputFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            OutputFormat::Avif => "image/avif",
+            OutputFormat::WebP => "image/webp",
+            OutputFormat::Jpeg => "image/jpeg",
+        }
+    }
+
+    /// Cache-key label for this format, used as the on-disk file extension.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Avif => "avif",
+            OutputFormat::WebP => "webp",
+            OutputFormat::Jpeg => "jpg",
+        }
+    }
+
+    fn image_format(self) -> image::ImageFormat {
+        match self {
+            OutputFormat::Avif => image::ImageFormat::Avif,
+            OutputFormat::WebP => image::ImageFormat::WebP,
+            OutputFormat::Jpeg => image::ImageFormat::Jpeg,
+        }
+    }
+}
+
+/// Pick the smallest format the client advertises support for via `Accept`,
+/// preferring AVIF over WebP over plain JPEG. Falls back to JPEG when the
+/// header is missing or names neither modern format.
+pub fn negotiate_format(accept: Option<&str>) -> OutputFormat {
+    let accept = match accept {
+        Some(accept) => accept,
+        None => return OutputFormat::Jpeg,
+    };
+
+    if accept.contains("image/avif") {
+        OutputFormat::Avif
+    } else if accept.contains("image/webp") {
+        OutputFormat::WebP
+    } else {
+        OutputFormat::Jpeg
+    }
+}
+
+/// Decode `source_bytes`, downscale it so its longest edge is at most
+/// `max_dimension` (left at native resolution when `None`), and encode the
+/// result in `format`.
+pub fn generate_thumbnail(source_bytes: &[u8], max_dimension: Option<u32>, format: OutputFormat) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory(source_bytes)?;
+    let thumbnail = match max_dimension {
+        Some(max_dimension) => image.resize(max_dimension, max_dimension, FilterType::Triangle),
+        None => image,
+    };
+
+    let mut buf = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut buf);
+    thumbnail.write_to(&mut cursor, format.image_format())?;
+
+    Ok(buf)
+}
+
+/// Write generated thumbnail bytes to the cache path, best-effort — a
+/// failure here shouldn't fail the request since we already have the bytes
+/// to serve.
+pub async fn cache_thumbnail(cache_path: &Path, bytes: &[u8]) {
+    if let Some(parent) = cache_path.parent() {
+        if let Err(e) = tokio::fs::create_dir_all(parent).await {
+            tracing::warn!("Failed to create thumbnail cache dir: {}", e);
+            return;
+        }
+    }
+
+    if let Err(e) = tokio::fs::write(cache_path, bytes).await {
+        tracing::warn!("Failed to write thumbnail cache file {:?}: {}", cache_path, e);
+    }
+}
+
+/// Tracks and generates on-demand thumbnails, at any of a bounded set of
+/// sizes and output formats.
+///
+/// Generation is CPU-bound (decode + resize), so it always runs on
+/// `spawn_blocking`; the `JobContainer` lets concurrent requests for the same
+/// `{id3}/{appid}/{filename}@{size}.{ext}` coalesce onto a single in-flight resize
+/// instead of racing to generate and write the same cache file.
+pub struct ThumbnailStore {
+    dir: PathBuf,
+    jobs: JobContainer,
+}
+
+impl ThumbnailStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn jobs(&self) -> JobContainer {
+        self.jobs.clone()
+    }
+
+    fn cache_path(&self, id3: u64, appid: u32, filename: &str, size_label: &str, format: OutputFormat) -> PathBuf {
+        let stem = Path::new(filename)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| filename.to_string());
+
+        self.dir
+            .join(id3.to_string())
+            .join(appid.to_string())
+            .join(format!("{}@{}.{}", stem, size_label, format.extension()))
+    }
+
+    /// Return the cached `size_label`/`format` thumbnail for
+    /// `{id3}/{appid}/{filename}`, generating it from `source` at
+    /// `max_dimension` first if necessary. Concurrent callers for the same
+    /// key coalesce onto whichever one of them wins the race to start the
+    /// job.
+    pub async fn get_or_generate(
+        &self,
+        storage: &Arc<dyn StorageProvider>,
+        id3: u64,
+        appid: u32,
+        filename: &str,
+        size_label: &str,
+        source: PathBuf,
+        max_dimension: Option<u32>,
+        format: OutputFormat,
+    ) -> anyhow::Result<Vec<u8>> {
+        let cache_path = self.cache_path(id3, appid, filename, size_label, format);
+
+        if let Ok(bytes) = tokio::fs::read(&cache_path).await {
+            return Ok(bytes);
+        }
+
+        let job_id = format!("{}/{}/{}@{}.{}", id3, appid, filename, size_label, format.extension());
+
+        let mut jobs = self.jobs.write().await;
+        let existing_notify = match jobs.get(&job_id) {
+            Some(job) if job.state == JobState::Running => Some(job.notify.clone()),
+            _ => {
+                jobs.insert(
+                    job_id.clone(),
+                    Job {
+                        source: source.clone(),
+                        state: JobState::Running,
+                        notify: Arc::new(Notify::new()),
+                    },
+                );
+                None
+            }
+        };
+
+        // Someone else is already generating this thumbnail: wait for them
+        // to finish instead of starting a duplicate resize. The waiter must
+        // be registered (`enable`d) while the jobs lock is still held, since
+        // the generator also needs that lock to call `notify_waiters` below
+        // -- otherwise a waiter that clones the `Notify` and only starts
+        // waiting on it after releasing the lock could have the generator's
+        // notification land in the gap and hang forever.
+        if let Some(notify) = existing_notify {
+            let notified = notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            drop(jobs);
+
+            notified.await;
+            return tokio::fs::read(&cache_path)
+                .await
+                .map_err(|_| anyhow::anyhow!("Thumbnail generation failed"));
+        }
+        drop(jobs);
+
+        let result = match crate::storage::read_all(storage.as_ref(), &source).await {
+            Ok(source_bytes) => {
+                tokio::task::spawn_blocking(move || generate_thumbnail(&source_bytes, max_dimension, format)).await
+            }
+            Err(e) => Ok(Err(e)),
+        };
+
+        let outcome = match result {
+            Ok(Ok(bytes)) => Ok(bytes),
+            Ok(Err(e)) => Err(e),
+            Err(e) => Err(anyhow::anyhow!("Thumbnail generation task panicked: {}", e)),
+        };
+
+        if let Ok(bytes) = &outcome {
+            cache_thumbnail(&cache_path, bytes).await;
+        }
+
+        if let Some(job) = self.jobs.write().await.get_mut(&job_id) {
+            job.state = if outcome.is_ok() { JobState::Done } else { JobState::Failed };
+            job.notify.notify_waiters();
+        }
+
+        outcome
+    }
+}
@@ -0,0 +1,208 @@
+//! Optional outbound tunnel so the screenshot explorer can be reached off the
+//! local network, the way VS Code's `code-tunnel` exposes a local server
+//! through an outbound relay connection instead of requiring inbound port
+//! forwarding.
+//!
+//! This dials `relay_host` itself (no inbound port forwarding required),
+//! registers the device code over that connection, and -- once the relay
+//! confirms registration -- forwards raw bytes between it and the local
+//! axum listener for as long as the tunnel runs. There's no real relay
+//! service behind `DEFAULT_RELAY_HOST` (an RFC 2606 `.invalid` address), so
+//! out of the box this always surfaces as `Failed` rather than a fabricated
+//! `Connected`; a deployment with a real relay speaking the wire protocol
+//! below can point `TUNNEL_RELAY_HOST` at it.
+//!
+//! # Wire protocol
+//!
+//! Deliberately minimal, since this has no real-world counterpart to match:
+//! the client sends `REGISTER <device_code>\n`, the relay replies with
+//! either `OK <url>\n` or `ERR <reason>\n`. After `OK`, the same connection
+//! becomes a raw byte pipe to the local server -- good enough for one
+//! concurrent visitor, which is all this feature is meant for.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::{watch, Mutex};
+use tokio_util::sync::CancellationToken;
+
+const DEFAULT_RELAY_HOST: &str = "tunnel.deck-screenshot-explorer.invalid";
+const RELAY_PORT: u16 = 7835;
+const REGISTER_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Current state of the tunnel connection, broadcast to anyone polling
+/// `/api/tunnel/status` or watching the channel directly.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum TunnelState {
+    Stopped,
+    Pairing { device_code: String },
+    Connected { url: String },
+    Failed { error: String },
+}
+
+pub struct TunnelManager {
+    relay_host: String,
+    /// `host:port` of the local axum listener, forwarded to once the relay
+    /// accepts registration.
+    local_addr: String,
+    state_tx: watch::Sender<TunnelState>,
+    state_rx: watch::Receiver<TunnelState>,
+    cancel: Mutex<Option<CancellationToken>>,
+}
+
+impl TunnelManager {
+    pub fn new(local_addr: String) -> Arc<Self> {
+        let relay_host =
+            std::env::var("TUNNEL_RELAY_HOST").unwrap_or_else(|_| DEFAULT_RELAY_HOST.to_string());
+        let (state_tx, state_rx) = watch::channel(TunnelState::Stopped);
+
+        Arc::new(Self {
+            relay_host,
+            local_addr,
+            state_tx,
+            state_rx,
+            cancel: Mutex::new(None),
+        })
+    }
+
+    pub fn state(&self) -> TunnelState {
+        self.state_rx.borrow().clone()
+    }
+
+    /// Start (or restart) the tunnel. Dials the relay, performs a
+    /// device-code pairing step, then keeps forwarding traffic until
+    /// `stop()` is called or the process shuts down.
+    pub async fn start(self: &Arc<Self>) {
+        let mut cancel_guard = self.cancel.lock().await;
+        if cancel_guard.is_some() {
+            tracing::info!("Tunnel already running, ignoring duplicate start request");
+            return;
+        }
+
+        let token = CancellationToken::new();
+        *cancel_guard = Some(token.clone());
+        drop(cancel_guard);
+
+        let manager = self.clone();
+        tokio::spawn(async move {
+            manager.run(token).await;
+        });
+    }
+
+    pub async fn stop(&self) {
+        let mut cancel_guard = self.cancel.lock().await;
+        if let Some(token) = cancel_guard.take() {
+            token.cancel();
+        }
+        let _ = self.state_tx.send(TunnelState::Stopped);
+    }
+
+    async fn run(self: Arc<Self>, cancel: CancellationToken) {
+        let device_code = generate_device_code();
+        tracing::info!(
+            "Pairing with tunnel relay {}: device code {}",
+            self.relay_host,
+            device_code
+        );
+        let _ = self.state_tx.send(TunnelState::Pairing {
+            device_code: device_code.clone(),
+        });
+
+        let relay_conn = match register_with_relay(&self.relay_host, &device_code).await {
+            Ok(registered) => registered,
+            Err(e) => {
+                tracing::error!("Failed to establish tunnel: {}", e);
+                let _ = self.state_tx.send(TunnelState::Failed {
+                    error: e.to_string(),
+                });
+                return;
+            }
+        };
+
+        tracing::info!("🌐 Tunnel established, reachable at: {}", relay_conn.url);
+        let _ = self.state_tx.send(TunnelState::Connected {
+            url: relay_conn.url,
+        });
+
+        tokio::select! {
+            result = forward_to_local(relay_conn.stream, &self.local_addr) => {
+                if let Err(e) = result {
+                    tracing::warn!("Tunnel connection dropped: {}", e);
+                }
+            }
+            _ = cancel.cancelled() => {}
+        }
+
+        tracing::info!("Tearing down tunnel connection");
+        let _ = self.state_tx.send(TunnelState::Stopped);
+    }
+}
+
+fn generate_device_code() -> String {
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+    (0..8)
+        .map(|_| {
+            let chars = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+            chars[rng.gen_range(0..chars.len())] as char
+        })
+        .collect()
+}
+
+/// A relay connection that has completed registration and is ready to be
+/// forwarded to the local server.
+struct RegisteredRelay {
+    stream: TcpStream,
+    url: String,
+}
+
+/// Dial the relay and register `device_code` over a real outbound TCP
+/// connection, returning the still-open stream so traffic can be forwarded
+/// over it. Never fabricates a URL: a relay that isn't there (the default
+/// host) or that doesn't speak the protocol above surfaces as an error.
+async fn register_with_relay(relay_host: &str, device_code: &str) -> anyhow::Result<RegisteredRelay> {
+    let addr = format!("{}:{}", relay_host, RELAY_PORT);
+    let mut stream = tokio::time::timeout(REGISTER_TIMEOUT, TcpStream::connect(&addr))
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out connecting to relay {}", addr))?
+        .map_err(|e| anyhow::anyhow!("failed to connect to relay {}: {}", addr, e))?;
+
+    stream
+        .write_all(format!("REGISTER {}\n", device_code).as_bytes())
+        .await?;
+
+    let mut reader = BufReader::new(&mut stream);
+    let mut response = String::new();
+    tokio::time::timeout(REGISTER_TIMEOUT, reader.read_line(&mut response))
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out waiting for relay {} to register", relay_host))??;
+
+    let response = response.trim_end();
+    match response.split_once(' ') {
+        Some(("OK", url)) => Ok(RegisteredRelay {
+            stream,
+            url: url.to_string(),
+        }),
+        Some(("ERR", reason)) => anyhow::bail!("relay rejected registration: {}", reason),
+        _ => anyhow::bail!("unexpected response from relay: {:?}", response),
+    }
+}
+
+/// Pipe bytes between an already-registered relay connection and a fresh
+/// connection to the local axum listener, in both directions, until either
+/// side closes.
+async fn forward_to_local(relay_stream: TcpStream, local_addr: &str) -> anyhow::Result<()> {
+    let local_stream = TcpStream::connect(local_addr).await?;
+    let (mut relay_read, mut relay_write) = relay_stream.into_split();
+    let (mut local_read, mut local_write) = local_stream.into_split();
+
+    tokio::select! {
+        result = tokio::io::copy(&mut relay_read, &mut local_write) => { result?; }
+        result = tokio::io::copy(&mut local_read, &mut relay_write) => { result?; }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Resize/format-conversion variants for served screenshots.
+//!
+//! Lets clients request `?w=&h=&format=&quality=` on a screenshot instead of
+//! always downloading the full-resolution capture. Generated variants are
+//! cached on disk next to the source, keyed by a hash of the source path and
+//! the requested parameters, so repeat requests for the same size are cheap.
+
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use image::imageops::FilterType;
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct VariantParams {
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+impl VariantParams {
+    pub fn is_empty(&self) -> bool {
+        self.w.is_none() && self.h.is_none() && self.format.is_none() && self.quality.is_none()
+    }
+
+    fn image_format(&self) -> image::ImageFormat {
+        match self.format.as_deref() {
+            Some("png") => image::ImageFormat::Png,
+            Some("webp") => image::ImageFormat::WebP,
+            _ => image::ImageFormat::Jpeg,
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self.format.as_deref() {
+            Some("png") => "png",
+            Some("webp") => "webp",
+            _ => "jpg",
+        }
+    }
+}
+
+/// Path to the cached variant file for a given source path and parameters.
+pub fn variant_cache_path(source: &Path, params: &VariantParams) -> PathBuf {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    source.hash(&mut hasher);
+    params.hash(&mut hasher);
+    let key = hasher.finish();
+
+    let cache_dir = source
+        .parent()
+        .map(|p| p.join("variants"))
+        .unwrap_or_else(|| PathBuf::from("variants"));
+
+    cache_dir.join(format!("{:016x}.{}", key, params.extension()))
+}
+
+/// Decode `source_bytes`, resize (preserving aspect ratio when only one of
+/// width/height is given) and re-encode per `params`.
+pub fn generate_variant(source_bytes: &[u8], params: &VariantParams) -> anyhow::Result<Vec<u8>> {
+    let image = image::load_from_memory(source_bytes)?;
+
+    let resized = match (params.w, params.h) {
+        (Some(w), Some(h)) => image.resize(w, h, FilterType::Triangle),
+        (Some(w), None) => image.resize(w, u32::MAX, FilterType::Triangle),
+        (None, Some(h)) => image.resize(u32::MAX, h, FilterType::Triangle),
+        (None, None) => image,
+    };
+
+    let mut buf = Vec::new();
+    let mut cursor = std::io::Cursor::new(&mut buf);
+
+    match params.image_format() {
+        image::ImageFormat::Jpeg => {
+            let quality = params.quality.unwrap_or(85);
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut cursor, quality);
+            encoder.encode_image(&resized)?;
+        }
+        format => {
+            resized.write_to(&mut cursor, format)?;
+        }
+    }
+
+    Ok(buf)
+}
+
+pub fn content_type_for(params: &VariantParams) -> &'static str {
+    match params.format.as_deref() {
+        Some("png") => "image/png",
+        Some("webp") => "image/webp",
+        _ => "image/jpeg",
+    }
+}
@@ -3,6 +3,7 @@
 //! This is heavily modified version from https://github.com/drguildo/vdfr
 //! Originally written using byteorder, this implementation use nom for parsing.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use nom::{
@@ -12,6 +13,7 @@ use nom::{
     sequence::tuple,
     IResult,
 };
+use serde::{de, ser};
 
 const BIN_NONE: u8 = b'\x00';
 const BIN_STRING: u8 = b'\x01';
@@ -39,6 +41,12 @@ pub enum VdfrError {
     UnknownMagic(u32),
     NomError(String),
     InvalidStringIndex(usize, usize),
+    SerdeError(String),
+    ChecksumMismatch {
+        app_id: u32,
+        expected: [u8; 20],
+        got: [u8; 20],
+    },
 }
 
 impl std::error::Error for VdfrError {}
@@ -53,16 +61,41 @@ impl std::fmt::Display for VdfrError {
             }
             VdfrError::ReadError(e) => e.fmt(f),
             VdfrError::NomError(e) => write!(f, "Nom error: {}", e),
+            VdfrError::SerdeError(e) => write!(f, "Serde error: {}", e),
+            VdfrError::ChecksumMismatch {
+                app_id,
+                expected,
+                got,
+            } => write!(
+                f,
+                "Checksum mismatch for app {}: expected {}, got {}",
+                app_id,
+                hex::encode(expected),
+                hex::encode(got)
+            ),
         }
     }
 }
 
+impl de::Error for VdfrError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        VdfrError::SerdeError(msg.to_string())
+    }
+}
+
+impl ser::Error for VdfrError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        VdfrError::SerdeError(msg.to_string())
+    }
+}
+
 impl From<std::io::Error> for VdfrError {
     fn from(e: std::io::Error) -> Self {
         VdfrError::ReadError(e)
     }
 }
 
+#[derive(Clone)]
 pub enum Value {
     StringType(String),
     WideStringType(String),
@@ -118,7 +151,156 @@ fn throw_error(error: nom::Err<nom::error::Error<&[u8]>>) -> VdfrError {
     }
 }
 
-type KeyValue = HashMap<String, Value>;
+/// Insertion-order-preserving map used for both [`KeyValue`] and
+/// [`BorrowedKeyValue`].
+///
+/// Valve's KeyValues format is order-sensitive: the binary/text writers emit
+/// keys in whatever order they're iterated, and [`App::verify`] only has a
+/// chance of reproducing the stored checksum if that's the order the file
+/// was originally parsed in. A `HashMap` can't promise that, so nodes are
+/// kept as a small `Vec` instead; appinfo/loginusers/shortcuts nodes are tiny
+/// (rarely more than a few dozen keys), so linear lookup is cheap relative to
+/// the parsing work around it.
+pub struct OrderedMap<K, V>(Vec<(K, V)>);
+
+impl<K: std::fmt::Debug, V: std::fmt::Debug> std::fmt::Debug for OrderedMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.0.iter().map(|(k, v)| (k, v))).finish()
+    }
+}
+
+impl<K, V> OrderedMap<K, V> {
+    fn new() -> Self {
+        OrderedMap(Vec::new())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &K> {
+        self.0.iter().map(|(k, _)| k)
+    }
+
+    fn iter(&self) -> OrderedMapIter<'_, K, V> {
+        OrderedMapIter(self.0.iter())
+    }
+}
+
+impl<K: PartialEq, V> OrderedMap<K, V> {
+    fn get<Q: PartialEq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+    {
+        self.0.iter().find(|(k, _)| k.borrow() == key).map(|(_, v)| v)
+    }
+
+    /// Insert `value` under `key`, overwriting any existing entry in place so
+    /// the original position (and therefore serialization order) is kept.
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(slot) => Some(std::mem::replace(&mut slot.1, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+}
+
+impl<K, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        OrderedMap(Vec::new())
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for OrderedMap<K, V> {
+    fn clone(&self) -> Self {
+        OrderedMap(self.0.clone())
+    }
+}
+
+impl<K: PartialEq, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrderedMap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<K, V> IntoIterator for OrderedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::vec::IntoIter<(K, V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = OrderedMapIter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+struct OrderedMapIter<'a, K, V>(std::slice::Iter<'a, (K, V)>);
+
+impl<'a, K, V> Iterator for OrderedMapIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k, v))
+    }
+}
+
+type KeyValue = OrderedMap<String, Value>;
+
+/// Zero-copy counterpart of [`Value`], borrowing string data straight out of
+/// the input buffer (or the `MAGIC_29` string pool) instead of cloning it.
+///
+/// Produced by [`parse_bytes_kv_borrowed`]; convert to the owned [`Value`]
+/// with [`BorrowedValue::into_owned`] once the data needs to outlive the
+/// buffer it was parsed from.
+pub enum BorrowedValue<'a> {
+    StringType(Cow<'a, str>),
+    WideStringType(Cow<'a, str>),
+    Int32Type(i32),
+    PointerType(i32),
+    ColorType(i32),
+    UInt64Type(u64),
+    Int64Type(i64),
+    Float32Type(f32),
+    KeyValueType(BorrowedKeyValue<'a>),
+}
+
+pub type BorrowedKeyValue<'a> = OrderedMap<Cow<'a, str>, BorrowedValue<'a>>;
+
+impl<'a> BorrowedValue<'a> {
+    pub fn into_owned(self) -> Value {
+        match self {
+            BorrowedValue::StringType(s) => Value::StringType(s.into_owned()),
+            BorrowedValue::WideStringType(s) => Value::WideStringType(s.into_owned()),
+            BorrowedValue::Int32Type(v) => Value::Int32Type(v),
+            BorrowedValue::PointerType(v) => Value::PointerType(v),
+            BorrowedValue::ColorType(v) => Value::ColorType(v),
+            BorrowedValue::UInt64Type(v) => Value::UInt64Type(v),
+            BorrowedValue::Int64Type(v) => Value::Int64Type(v),
+            BorrowedValue::Float32Type(v) => Value::Float32Type(v),
+            BorrowedValue::KeyValueType(kv) => Value::KeyValueType(into_owned_kv(kv)),
+        }
+    }
+}
+
+fn into_owned_kv(kv: BorrowedKeyValue) -> KeyValue {
+    kv.into_iter()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect()
+}
 
 /// Options for reading key-value data.
 #[derive(Debug, Clone, Default)]
@@ -148,7 +330,7 @@ fn find_keys<'a>(kv: &'a KeyValue, keys: &[&str]) -> Option<&'a Value> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct App {
     pub id: u32,
     pub size: u32,
@@ -169,6 +351,65 @@ pub struct AppInfo {
 }
 
 impl AppInfo {
+    /// Serialize back to the binary `appinfo.vdf` format this was loaded
+    /// from, re-encoding every app's key-values and (for `MAGIC_29`)
+    /// rebuilding a deduplicated key string pool from scratch.
+    ///
+    /// App headers (`size`, `checksum_*`, ...) are taken from the `App`
+    /// as-is except for `size`, which is recomputed from the re-encoded
+    /// key-values so editing a name or value still produces a loadable file.
+    pub fn write(&self) -> Result<Vec<u8>, VdfrError> {
+        let mut apps: Vec<&App> = self.apps.values().collect();
+        apps.sort_by_key(|app| app.id);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.universe.to_le_bytes());
+
+        match self.version {
+            MAGIC_27 | MAGIC_28 => {
+                let options = KeyValueOptions::default();
+                for app in &apps {
+                    write_app(&mut out, app, &options, self.version);
+                }
+                out.extend_from_slice(&0u32.to_le_bytes());
+            }
+            MAGIC_29 => {
+                let mut string_pool = Vec::new();
+                let mut pool_index = HashMap::new();
+                for app in &apps {
+                    collect_pool_keys(&app.key_values, &mut string_pool, &mut pool_index);
+                }
+                let options = KeyValueOptions {
+                    string_pool: string_pool.clone(),
+                    alt_format: false,
+                };
+
+                let mut payload = Vec::new();
+                for app in &apps {
+                    write_app(&mut payload, app, &options, self.version);
+                }
+                payload.extend_from_slice(&0u32.to_le_bytes());
+
+                let mut pool_bytes = Vec::new();
+                pool_bytes.extend_from_slice(&(string_pool.len() as u32).to_le_bytes());
+                for s in &string_pool {
+                    pool_bytes.extend_from_slice(&write_utf8(s));
+                }
+
+                // offset is measured from the start of the file (version + universe + offset itself).
+                let header_size = 4usize + 4 + 8;
+                let offset = (header_size + payload.len()) as i64;
+                out.extend_from_slice(&offset.to_le_bytes());
+                out.extend_from_slice(&payload);
+                out.extend_from_slice(&pool_bytes);
+            }
+            _ => return Err(VdfrError::UnknownMagic(self.version)),
+        }
+
+        Ok(out)
+    }
+
     pub fn load(data: &[u8]) -> Result<AppInfo, VdfrError> {
         let (data, (version, universe)) = tuple((le_u32, le_u32))(data).map_err(throw_error)?;
 
@@ -211,6 +452,142 @@ impl AppInfo {
             apps,
         })
     }
+
+    /// Run [`App::verify`] over every loaded app, stopping at the first
+    /// checksum mismatch.
+    pub fn verify_all(&self) -> Result<(), VdfrError> {
+        for app in self.apps.values() {
+            app.verify()?;
+        }
+        Ok(())
+    }
+
+    /// Build an index of per-app byte offsets instead of eagerly parsing
+    /// every app's key-values, so a caller that only needs one title's
+    /// metadata isn't stuck paying for the rest.
+    ///
+    /// `MAGIC_29`'s upfront string pool makes this cheap: the scan only has
+    /// to walk past each app's bytes (without allocating its key-values) to
+    /// find the next header. `MAGIC_27`/`MAGIC_28` have no such pool to
+    /// decode against, so they fall back to the eager [`AppInfo::load`] path
+    /// and [`AppInfoIndex::get`] just clones out of the result.
+    pub fn open(data: &[u8]) -> Result<AppInfoIndex<'_>, VdfrError> {
+        let (data, (version, _universe)) = tuple((le_u32, le_u32))(data).map_err(throw_error)?;
+
+        match version {
+            MAGIC_29 => {
+                let (data, offset) = le_i64(data).map_err(throw_error)?;
+
+                let read_amount = 4usize + 4 + 8;
+                let offset_actual = (offset as usize) - read_amount;
+                let (string_pools, payload) = take(offset_actual)(data).map_err(throw_error)?;
+                let (string_pools, count) = le_u32(string_pools).map_err(throw_error)?;
+
+                let (_, string_pool) =
+                    read_string_pools(string_pools, count as usize).map_err(throw_error)?;
+
+                let options = KeyValueOptions {
+                    string_pool,
+                    alt_format: false,
+                };
+                let (_, offsets) = scan_app_offsets(payload, &options, version).map_err(throw_error)?;
+
+                Ok(AppInfoIndex::Lazy {
+                    payload,
+                    options,
+                    version,
+                    offsets,
+                })
+            }
+            MAGIC_27 | MAGIC_28 => {
+                let options = KeyValueOptions::default();
+                let (_, mut apps) = parse_apps(data, &options, version).map_err(throw_error)?;
+                apps.remove(&0);
+                Ok(AppInfoIndex::Eager(apps))
+            }
+            _ => Err(VdfrError::UnknownMagic(version)),
+        }
+    }
+}
+
+/// An index built by [`AppInfo::open`] for O(1) single-app lookups.
+pub enum AppInfoIndex<'a> {
+    Lazy {
+        payload: &'a [u8],
+        options: KeyValueOptions,
+        version: u32,
+        offsets: HashMap<u32, usize>,
+    },
+    Eager(HashMap<u32, App>),
+}
+
+impl<'a> AppInfoIndex<'a> {
+    /// Look up a single app by id. For `MAGIC_29` files this parses only
+    /// that app's key-values region against the shared string pool, without
+    /// touching any other app's bytes.
+    pub fn get(&self, app_id: u32) -> Option<Result<App, VdfrError>> {
+        match self {
+            AppInfoIndex::Lazy {
+                payload,
+                options,
+                version,
+                offsets,
+            } => {
+                let start = *offsets.get(&app_id)?;
+                Some(
+                    parse_app(&payload[start..], options, *version)
+                        .map(|(_, app)| app)
+                        .map_err(throw_error),
+                )
+            }
+            AppInfoIndex::Eager(apps) => apps.get(&app_id).cloned().map(Ok),
+        }
+    }
+}
+
+/// Scan a `MAGIC_29` apps payload and record each app's starting byte offset
+/// (relative to `data`), skipping every app's key-values without decoding
+/// them into `Value`s.
+fn scan_app_offsets(
+    data: &[u8],
+    options: &KeyValueOptions,
+    version: u32,
+) -> IResult<&[u8], HashMap<u32, usize>> {
+    let mut offsets = HashMap::new();
+    let mut rest = data;
+
+    loop {
+        let start_offset = data.len() - rest.len();
+        let (after_id, app_id) = le_u32(rest)?;
+        if app_id == 0 {
+            return Ok((after_id, offsets));
+        }
+        offsets.insert(app_id, start_offset);
+
+        let (after_header, (_size, _state, _last_update, _access_token)) =
+            tuple((le_u32, le_u32, le_u32, le_u64))(after_id)?;
+        let (after_checksum_txt, _checksum_txt) = take(20usize)(after_header)?;
+        let (after_change_number, _change_number) = le_u32(after_checksum_txt)?;
+        let after_checksum_bin = match version {
+            MAGIC_27 => after_change_number,
+            _ => take(20usize)(after_change_number)?.0,
+        };
+
+        let (after_kv, _) = skip_bytes_kv(after_checksum_bin, options)?;
+        rest = after_kv;
+    }
+}
+
+fn sha1_digest(data: &[u8]) -> [u8; 20] {
+    use sha1::{Digest, Sha1};
+
+    let mut hasher = Sha1::new();
+    hasher.update(data);
+    let digest = hasher.finalize();
+
+    let mut out = [0u8; 20];
+    out.copy_from_slice(&digest);
+    out
 }
 
 impl App {
@@ -218,6 +595,48 @@ impl App {
         find_keys(&self.key_values, keys)
     }
 
+    /// Deserialize the key-values found at `keys` (see [`App::get`]) into a
+    /// typed struct, e.g. `app.deserialize::<CommonSection>(&["appinfo", "common"])`,
+    /// instead of chaining `get`/`match Value::...` by hand.
+    pub fn deserialize<T: de::DeserializeOwned>(&self, keys: &[&str]) -> Result<T, VdfrError> {
+        let value = self
+            .get(keys)
+            .ok_or_else(|| VdfrError::SerdeError(format!("key path {:?} not found", keys)))?;
+        T::deserialize(value)
+    }
+
+    /// Re-serialize this app's key-values in both the text and binary
+    /// KeyValues encodings and compare their SHA-1 digests against the
+    /// stored `checksum_txt`/`checksum_bin`, catching truncated or tampered
+    /// `appinfo.vdf` data that would otherwise parse silently. This only
+    /// round-trips byte-for-byte because `key_values` is an [`OrderedMap`],
+    /// preserving the key order it was originally parsed in.
+    pub fn verify(&self) -> Result<(), VdfrError> {
+        let text = write_text_keyvalues(&self.key_values);
+        let text_digest = sha1_digest(text.as_bytes());
+        if text_digest != self.checksum_txt {
+            return Err(VdfrError::ChecksumMismatch {
+                app_id: self.id,
+                expected: self.checksum_txt,
+                got: text_digest,
+            });
+        }
+
+        if let Some(expected_bin) = self.checksum_bin {
+            let binary = write_keyvalues(&self.key_values, &KeyValueOptions::default());
+            let binary_digest = sha1_digest(&binary);
+            if binary_digest != expected_bin {
+                return Err(VdfrError::ChecksumMismatch {
+                    app_id: self.id,
+                    expected: expected_bin,
+                    got: binary_digest,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
     /// Get the name of the app.
     pub fn app_name(&self) -> Option<String> {
         let name = self.get(&["appinfo", "common", "name"]);
@@ -249,6 +668,39 @@ impl App {
     }
 }
 
+/// Walk an app's key-values and append any keys not already in `pool` to it,
+/// recording their index in `index` so repeated keys are written once.
+fn collect_pool_keys(kv: &KeyValue, pool: &mut Vec<String>, index: &mut HashMap<String, usize>) {
+    for (key, value) in kv {
+        if !index.contains_key(key) {
+            index.insert(key.clone(), pool.len());
+            pool.push(key.clone());
+        }
+        if let Value::KeyValueType(sub) = value {
+            collect_pool_keys(sub, pool, index);
+        }
+    }
+}
+
+fn write_app(out: &mut Vec<u8>, app: &App, options: &KeyValueOptions, version: u32) {
+    let kv_bytes = write_keyvalues(&app.key_values, options);
+
+    let mut body = Vec::new();
+    body.extend_from_slice(&app.state.to_le_bytes());
+    body.extend_from_slice(&app.last_update.to_le_bytes());
+    body.extend_from_slice(&app.access_token.to_le_bytes());
+    body.extend_from_slice(&app.checksum_txt);
+    body.extend_from_slice(&app.change_number.to_le_bytes());
+    if version != MAGIC_27 {
+        body.extend_from_slice(&app.checksum_bin.unwrap_or([0; 20]));
+    }
+    body.extend_from_slice(&kv_bytes);
+
+    out.extend_from_slice(&app.id.to_le_bytes());
+    out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    out.extend_from_slice(&body);
+}
+
 fn parse_apps<'a>(
     data: &'a [u8],
     options: &'a KeyValueOptions,
@@ -281,7 +733,7 @@ fn parse_app<'a>(
                 checksum_txt: [0; 20],
                 checksum_bin: Some([0; 20]),
                 change_number: 0,
-                key_values: HashMap::new(),
+                key_values: KeyValue::new(),
             },
         ))
     } else {
@@ -325,14 +777,38 @@ pub fn parse_keyvalues(data: &[u8]) -> Result<KeyValue, VdfrError> {
     Ok(key_values)
 }
 
+/// Zero-copy counterpart of [`parse_keyvalues`] for callers that want to
+/// avoid cloning every key/value out of the string pool and input buffer.
+pub fn parse_keyvalues_borrowed(data: &[u8]) -> Result<BorrowedKeyValue<'_>, VdfrError> {
+    let (_, key_values) =
+        parse_bytes_kv_borrowed(data, &KeyValueOptions::default()).map_err(throw_error)?;
+    Ok(key_values)
+}
+
+/// Thin compatibility wrapper over [`parse_bytes_kv_borrowed`] for callers
+/// that need owned data. Kept around so the rest of the module (the writer,
+/// text converter, serde bridge, checksum verification, ...) doesn't have to
+/// care about borrowing.
 fn parse_bytes_kv<'a>(data: &'a [u8], options: &'a KeyValueOptions) -> IResult<&'a [u8], KeyValue> {
+    let (rest, node) = parse_bytes_kv_borrowed(data, options)?;
+    Ok((rest, into_owned_kv(node)))
+}
+
+/// Parse a `BIN_*` tag stream, borrowing string-pool entries and UTF-8 slices
+/// straight out of `data`/`options` instead of cloning them. Only
+/// `BIN_WIDESTRING` values still allocate, since decoding UTF-16 always
+/// produces a new `String`.
+fn parse_bytes_kv_borrowed<'a>(
+    data: &'a [u8],
+    options: &'a KeyValueOptions,
+) -> IResult<&'a [u8], BorrowedKeyValue<'a>> {
     let bin_end = if options.alt_format {
         BIN_END_ALT
     } else {
         BIN_END
     };
 
-    let mut node = KeyValue::new();
+    let mut node = BorrowedKeyValue::new();
 
     let mut data = data;
     loop {
@@ -343,7 +819,7 @@ fn parse_bytes_kv<'a>(data: &'a [u8], options: &'a KeyValueOptions) -> IResult<&
         }
 
         let (res, key) = if options.string_pool.is_empty() {
-            parse_utf8(res)?
+            parse_utf8_borrowed(res)?
         } else {
             let (res, index) = le_u32(res)?;
             let index = index as usize;
@@ -353,43 +829,43 @@ fn parse_bytes_kv<'a>(data: &'a [u8], options: &'a KeyValueOptions) -> IResult<&
                     nom::error::ErrorKind::Eof,
                 )));
             }
-            (res, options.string_pool[index].clone())
+            (res, Cow::Borrowed(options.string_pool[index].as_str()))
         };
 
         let (res, value) = match bin {
             BIN_NONE => {
-                let (res, subnode) = parse_bytes_kv(res, options)?;
-                (res, Value::KeyValueType(subnode))
+                let (res, subnode) = parse_bytes_kv_borrowed(res, options)?;
+                (res, BorrowedValue::KeyValueType(subnode))
             }
             BIN_STRING => {
-                let (res, value) = parse_utf8(res)?;
-                (res, Value::StringType(value))
+                let (res, value) = parse_utf8_borrowed(res)?;
+                (res, BorrowedValue::StringType(value))
             }
             BIN_WIDESTRING => {
                 let (res, value) = parse_utf16(res)?;
-                (res, Value::WideStringType(value))
+                (res, BorrowedValue::WideStringType(Cow::Owned(value)))
             }
             BIN_INT32 | BIN_POINTER | BIN_COLOR => {
                 let (res, value) = le_i32(res)?;
                 let value = match bin {
-                    BIN_INT32 => Value::Int32Type(value),
-                    BIN_POINTER => Value::PointerType(value),
-                    BIN_COLOR => Value::ColorType(value),
+                    BIN_INT32 => BorrowedValue::Int32Type(value),
+                    BIN_POINTER => BorrowedValue::PointerType(value),
+                    BIN_COLOR => BorrowedValue::ColorType(value),
                     _ => unreachable!(),
                 };
                 (res, value)
             }
             BIN_UINT64 => {
                 let (res, value) = le_u64(res)?;
-                (res, Value::UInt64Type(value))
+                (res, BorrowedValue::UInt64Type(value))
             }
             BIN_INT64 => {
                 let (res, value) = le_i64(res)?;
-                (res, Value::Int64Type(value))
+                (res, BorrowedValue::Int64Type(value))
             }
             BIN_FLOAT32 => {
                 let (res, value) = le_f32(res)?;
-                (res, Value::Float32Type(value))
+                (res, BorrowedValue::Float32Type(value))
             }
             _ => {
                 return Err(nom::Err::Error(nom::error::Error::new(
@@ -404,6 +880,179 @@ fn parse_bytes_kv<'a>(data: &'a [u8], options: &'a KeyValueOptions) -> IResult<&
     }
 }
 
+/// Skip over a `BIN_*` tag stream without building the nested [`OrderedMap`]s
+/// `parse_bytes_kv` would, used by [`scan_app_offsets`] to find app
+/// boundaries. Only called against `MAGIC_29` data, where every key is a
+/// `le_u32` string-pool index rather than an inline string, so keys in
+/// particular are skipped without ever being allocated.
+fn skip_bytes_kv<'a>(data: &'a [u8], options: &KeyValueOptions) -> IResult<&'a [u8], ()> {
+    let bin_end = if options.alt_format {
+        BIN_END_ALT
+    } else {
+        BIN_END
+    };
+
+    let mut data = data;
+    loop {
+        let (res, bin) = le_u8(data)?;
+
+        if bin == bin_end {
+            return Ok((res, ()));
+        }
+
+        let (res, _key_index) = le_u32(res)?;
+        let (res, ()) = skip_value_bytes(res, bin, options)?;
+        data = res;
+    }
+}
+
+fn skip_value_bytes<'a>(
+    data: &'a [u8],
+    bin: u8,
+    options: &KeyValueOptions,
+) -> IResult<&'a [u8], ()> {
+    match bin {
+        BIN_NONE => skip_bytes_kv(data, options),
+        BIN_STRING => {
+            let (data, _) = parse_utf8(data)?;
+            Ok((data, ()))
+        }
+        BIN_WIDESTRING => {
+            let (data, _) = parse_utf16(data)?;
+            Ok((data, ()))
+        }
+        BIN_INT32 | BIN_POINTER | BIN_COLOR | BIN_FLOAT32 => {
+            let (data, _) = take(4usize)(data)?;
+            Ok((data, ()))
+        }
+        BIN_UINT64 | BIN_INT64 => {
+            let (data, _) = take(8usize)(data)?;
+            Ok((data, ()))
+        }
+        _ => Err(nom::Err::Error(nom::error::Error::new(
+            data,
+            nom::error::ErrorKind::Char,
+        ))),
+    }
+}
+
+/// Encode a `KeyValue` node back into the `BIN_*` tag stream, including its
+/// trailing terminator byte. If `options.string_pool` is non-empty, keys are
+/// written as `le_u32` indices into it instead of inline UTF-8 strings.
+pub fn write_keyvalues(kv: &KeyValue, options: &KeyValueOptions) -> Vec<u8> {
+    let bin_end = if options.alt_format {
+        BIN_END_ALT
+    } else {
+        BIN_END
+    };
+
+    let key_index: Option<HashMap<&str, u32>> = if options.string_pool.is_empty() {
+        None
+    } else {
+        Some(
+            options
+                .string_pool
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (s.as_str(), i as u32))
+                .collect(),
+        )
+    };
+
+    let mut out = Vec::new();
+    for (key, value) in kv {
+        write_key_value(&mut out, key, value, options, key_index.as_ref());
+    }
+    out.push(bin_end);
+    out
+}
+
+fn write_key(out: &mut Vec<u8>, key: &str, key_index: Option<&HashMap<&str, u32>>) {
+    match key_index {
+        Some(index) => {
+            let index = *index
+                .get(key)
+                .unwrap_or_else(|| panic!("key {:?} missing from string pool", key));
+            out.extend_from_slice(&index.to_le_bytes());
+        }
+        None => out.extend_from_slice(&write_utf8(key)),
+    }
+}
+
+fn write_key_value(
+    out: &mut Vec<u8>,
+    key: &str,
+    value: &Value,
+    options: &KeyValueOptions,
+    key_index: Option<&HashMap<&str, u32>>,
+) {
+    match value {
+        Value::KeyValueType(sub) => {
+            out.push(BIN_NONE);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&write_keyvalues(sub, options));
+        }
+        Value::StringType(s) => {
+            out.push(BIN_STRING);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&write_utf8(s));
+        }
+        Value::WideStringType(s) => {
+            out.push(BIN_WIDESTRING);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&write_utf16(s));
+        }
+        Value::Int32Type(v) => {
+            out.push(BIN_INT32);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::PointerType(v) => {
+            out.push(BIN_POINTER);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::ColorType(v) => {
+            out.push(BIN_COLOR);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::UInt64Type(v) => {
+            out.push(BIN_UINT64);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Int64Type(v) => {
+            out.push(BIN_INT64);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+        Value::Float32Type(v) => {
+            out.push(BIN_FLOAT32);
+            write_key(out, key, key_index);
+            out.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+}
+
+fn write_utf8(s: &str) -> Vec<u8> {
+    let mut out = s.as_bytes().to_vec();
+    out.push(0);
+    out
+}
+
+fn write_utf16(s: &str) -> Vec<u8> {
+    // parse_utf16 reads big-endian without a BOM when none is present, and
+    // leaves a trailing '\0' char on the decoded String; strip it back off
+    // here so round-tripping doesn't accumulate extra NULs.
+    let mut out = Vec::new();
+    for unit in s.trim_end_matches('\0').encode_utf16() {
+        out.extend_from_slice(&unit.to_be_bytes());
+    }
+    out.extend_from_slice(&0u16.to_be_bytes());
+    out
+}
+
 fn read_string_pools(data: &[u8], amount: usize) -> IResult<&[u8], Vec<String>> {
     count(parse_utf8, amount)(data)
 }
@@ -417,6 +1066,17 @@ fn parse_utf8(input: &[u8]) -> IResult<&[u8], String> {
     Ok((rest, s.to_string()))
 }
 
+/// Like [`parse_utf8`], but borrows the string slice directly from `input`
+/// instead of allocating a new `String`.
+fn parse_utf8_borrowed(input: &[u8]) -> IResult<&[u8], Cow<'_, str>> {
+    // Parse until NULL byte
+    let (rest, buf) = take_until("\0")(input)?;
+    let (rest, _) = le_u8(rest)?; // Skip NULL byte
+    let s = std::str::from_utf8(buf)
+        .map_err(|_| nom::Err::Error(nom::error::Error::new(rest, nom::error::ErrorKind::Char)))?;
+    Ok((rest, Cow::Borrowed(s)))
+}
+
 enum Endian {
     Be,
     Le,
@@ -461,3 +1121,693 @@ fn parse_utf16(input: &[u8]) -> IResult<&[u8], String> {
     let s = std::string::String::from_utf16_lossy(&v);
     Ok((rest, s))
 }
+
+// Text KeyValues (.vdf), e.g. `loginusers.vdf`, `config.vdf`. Unlike the
+// binary format, every leaf value is a quoted string; only `StringType` and
+// `KeyValueType` ever appear in the result.
+
+/// Parse Valve's human-readable KeyValues text format (`"key" "value"` pairs
+/// and `"key" { ... }` blocks, `//` line comments, `\"`/`\\` escapes).
+pub fn parse_text_keyvalues(input: &str) -> Result<KeyValue, VdfrError> {
+    let mut chars = input.chars().peekable();
+    parse_text_kv_body(&mut chars)
+}
+
+fn skip_text_whitespace_and_comments(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    loop {
+        match chars.peek() {
+            Some(c) if c.is_whitespace() => {
+                chars.next();
+            }
+            Some('/') => {
+                let mut lookahead = chars.clone();
+                lookahead.next();
+                if lookahead.peek() == Some(&'/') {
+                    for c in chars.by_ref() {
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                } else {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
+
+fn parse_text_quoted_string(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<String, VdfrError> {
+    match chars.next() {
+        Some('"') => {}
+        other => {
+            return Err(VdfrError::NomError(format!(
+                "expected opening quote, got {:?}",
+                other
+            )))
+        }
+    }
+
+    let mut s = String::new();
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some('"') => s.push('"'),
+                Some('\\') => s.push('\\'),
+                Some(other) => {
+                    s.push('\\');
+                    s.push(other);
+                }
+                None => {
+                    return Err(VdfrError::NomError(
+                        "unexpected end of input in string escape".to_string(),
+                    ))
+                }
+            },
+            Some('"') => return Ok(s),
+            Some(c) => s.push(c),
+            None => {
+                return Err(VdfrError::NomError(
+                    "unterminated string literal".to_string(),
+                ))
+            }
+        }
+    }
+}
+
+fn parse_text_kv_body(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<KeyValue, VdfrError> {
+    let mut node = KeyValue::new();
+
+    loop {
+        skip_text_whitespace_and_comments(chars);
+        match chars.peek() {
+            None => return Ok(node),
+            Some('}') => {
+                chars.next();
+                return Ok(node);
+            }
+            Some('"') => {
+                let key = parse_text_quoted_string(chars)?;
+                skip_text_whitespace_and_comments(chars);
+                match chars.peek() {
+                    Some('"') => {
+                        let value = parse_text_quoted_string(chars)?;
+                        node.insert(key, Value::StringType(value));
+                    }
+                    Some('{') => {
+                        chars.next();
+                        let sub = parse_text_kv_body(chars)?;
+                        node.insert(key, Value::KeyValueType(sub));
+                    }
+                    other => {
+                        return Err(VdfrError::NomError(format!(
+                            "expected value for key {:?}, got {:?}",
+                            key, other
+                        )))
+                    }
+                }
+            }
+            other => {
+                return Err(VdfrError::NomError(format!(
+                    "expected a key or closing brace, got {:?}",
+                    other
+                )))
+            }
+        }
+    }
+}
+
+/// Render a `KeyValue` node as Valve's KeyValues text format, tab-indented to
+/// match what Steam itself writes.
+pub fn write_text_keyvalues(kv: &KeyValue) -> String {
+    let mut out = String::new();
+    write_text_kv_body(kv, 0, &mut out);
+    out
+}
+
+fn write_text_value(value: &Value) -> String {
+    match value {
+        Value::StringType(s) => fmt_string(s),
+        Value::WideStringType(s) => fmt_string(s),
+        Value::Int32Type(i) => i.to_string(),
+        Value::PointerType(i) => format!("*{}", i),
+        Value::ColorType(i) => i.to_string(),
+        Value::UInt64Type(i) => i.to_string(),
+        Value::Int64Type(i) => i.to_string(),
+        Value::Float32Type(i) => i.to_string(),
+        Value::KeyValueType(_) => unreachable!("nested KeyValueType has no inline text value"),
+    }
+}
+
+fn write_text_kv_body(kv: &KeyValue, depth: usize, out: &mut String) {
+    let indent = "\t".repeat(depth);
+    for (key, value) in kv {
+        out.push_str(&indent);
+        match value {
+            Value::KeyValueType(sub) => {
+                out.push_str(&format!("\"{}\"\n", fmt_string(key)));
+                out.push_str(&indent);
+                out.push_str("{\n");
+                write_text_kv_body(sub, depth + 1, out);
+                out.push_str(&indent);
+                out.push_str("}\n");
+            }
+            _ => {
+                out.push_str(&format!(
+                    "\"{}\"\t\t\"{}\"\n",
+                    fmt_string(key),
+                    write_text_value(value)
+                ));
+            }
+        }
+    }
+}
+
+// serde support, so callers can pull typed structs out of a `Value`/`KeyValue`
+// instead of chaining `get`/`match Value::...` by hand (see `App::deserialize`).
+// `KeyValueType` nodes deserialize as maps, except when every key parses as an
+// index, in which case they're treated as a sequence -- that's how Steam itself
+// encodes arrays (`"0" {...} "1" {...}`).
+
+fn is_sequence_like(kv: &KeyValue) -> bool {
+    !kv.is_empty() && kv.keys().all(|k| k.parse::<usize>().is_ok())
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a Value {
+    type Error = VdfrError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self {
+            Value::StringType(s) | Value::WideStringType(s) => visitor.visit_string(s.clone()),
+            Value::Int32Type(v) => visitor.visit_i32(*v),
+            Value::PointerType(v) => visitor.visit_i32(*v),
+            Value::ColorType(v) => visitor.visit_i32(*v),
+            Value::UInt64Type(v) => visitor.visit_u64(*v),
+            Value::Int64Type(v) => visitor.visit_i64(*v),
+            Value::Float32Type(v) => visitor.visit_f32(*v),
+            Value::KeyValueType(kv) if is_sequence_like(kv) => visitor.visit_seq(KvSeqAccess::new(kv)),
+            Value::KeyValueType(kv) => visitor.visit_map(KvMapAccess::new(kv)),
+        }
+    }
+
+    // Every `Value` we can reach here is a value that was actually present in
+    // the parsed KeyValues tree (there's no `Value::None` variant -- a
+    // missing key just means the entry is absent from the map, which
+    // `KvMapAccess`/struct field lookup already treats as `None` without
+    // going through this at all). So an `Option<T>` field always sees a
+    // *present* value when we get this far, and should deserialize as `Some`.
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct KeyDeserializer<'a>(&'a str);
+
+impl<'de, 'a> de::Deserializer<'de> for KeyDeserializer<'a> {
+    type Error = VdfrError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_str(self.0)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct KvMapAccess<'a> {
+    iter: OrderedMapIter<'a, String, Value>,
+    value: Option<&'a Value>,
+}
+
+impl<'a> KvMapAccess<'a> {
+    fn new(kv: &'a KeyValue) -> Self {
+        KvMapAccess {
+            iter: kv.iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for KvMapAccess<'a> {
+    type Error = VdfrError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(value)
+    }
+}
+
+struct KvSeqAccess<'a> {
+    items: std::vec::IntoIter<&'a Value>,
+}
+
+impl<'a> KvSeqAccess<'a> {
+    fn new(kv: &'a KeyValue) -> Self {
+        let mut indexed: Vec<(usize, &Value)> = kv
+            .iter()
+            .filter_map(|(k, v)| k.parse::<usize>().ok().map(|i| (i, v)))
+            .collect();
+        indexed.sort_by_key(|(i, _)| *i);
+
+        KvSeqAccess {
+            items: indexed
+                .into_iter()
+                .map(|(_, v)| v)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for KvSeqAccess<'a> {
+    type Error = VdfrError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.items.next() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Serialize any `Serialize` value into a `Value`, the inverse of
+/// [`App::deserialize`]. Structs and maps become `Value::KeyValueType`;
+/// sequences become a `Value::KeyValueType` keyed by index, matching how
+/// Steam itself encodes arrays.
+pub fn to_value<T: ser::Serialize + ?Sized>(value: &T) -> Result<Value, VdfrError> {
+    value.serialize(ValueSerializer)
+}
+
+struct ValueSerializer;
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, VdfrError> {
+        Ok(Value::Int64Type(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, VdfrError> {
+        Ok(Value::Int32Type(v as i32))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, VdfrError> {
+        Ok(Value::UInt64Type(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, VdfrError> {
+        Ok(Value::Float32Type(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, VdfrError> {
+        Ok(Value::Float32Type(v as f32))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, VdfrError> {
+        Ok(Value::StringType(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, VdfrError> {
+        Ok(Value::StringType(v.to_string()))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Value, VdfrError> {
+        Err(VdfrError::SerdeError("byte arrays are not supported".to_string()))
+    }
+
+    fn serialize_none(self) -> Result<Value, VdfrError> {
+        Err(VdfrError::SerdeError(
+            "cannot serialize an absent value on its own".to_string(),
+        ))
+    }
+
+    fn serialize_some<T: ser::Serialize + ?Sized>(self, value: &T) -> Result<Value, VdfrError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, VdfrError> {
+        Err(VdfrError::SerdeError("unit values are not supported".to_string()))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, VdfrError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, VdfrError> {
+        Ok(Value::StringType(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, VdfrError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ser::Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Value, VdfrError> {
+        Err(VdfrError::SerdeError("enum variants are not supported".to_string()))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqSerializer, VdfrError> {
+        Ok(SeqSerializer::default())
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer, VdfrError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, VdfrError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<SeqSerializer, VdfrError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer, VdfrError> {
+        Ok(MapSerializer::default())
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapSerializer, VdfrError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> Result<MapSerializer, VdfrError> {
+        self.serialize_map(Some(len))
+    }
+}
+
+#[derive(Default)]
+struct SeqSerializer {
+    items: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), VdfrError> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        let kv: KeyValue = self
+            .items
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect();
+        Ok(Value::KeyValueType(kv))
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_element<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), VdfrError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), VdfrError> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), VdfrError> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[derive(Default)]
+struct MapSerializer {
+    entries: KeyValue,
+    next_key: Option<String>,
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_key<T: ser::Serialize + ?Sized>(&mut self, key: &T) -> Result<(), VdfrError> {
+        self.next_key = Some(match to_value(key)? {
+            Value::StringType(s) | Value::WideStringType(s) => s,
+            other => format!("{:?}", other),
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: ser::Serialize + ?Sized>(&mut self, value: &T) -> Result<(), VdfrError> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.entries.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        Ok(Value::KeyValueType(self.entries))
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), VdfrError> {
+        self.entries.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        Ok(Value::KeyValueType(self.entries))
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Value;
+    type Error = VdfrError;
+
+    fn serialize_field<T: ser::Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), VdfrError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Value, VdfrError> {
+        ser::SerializeStruct::end(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_keyvalues_round_trip() {
+        let mut inner = KeyValue::new();
+        inner.insert("name".to_string(), Value::StringType("Half-Life".to_string()));
+        inner.insert("count".to_string(), Value::Int32Type(42));
+
+        let mut kv = KeyValue::new();
+        kv.insert("common".to_string(), Value::KeyValueType(inner));
+
+        let bytes = write_keyvalues(&kv, &KeyValueOptions::default());
+        let parsed = parse_keyvalues(&bytes).unwrap();
+
+        let Some(Value::KeyValueType(inner)) = parsed.get("common") else {
+            panic!("expected \"common\" to round-trip as a nested key-value");
+        };
+        let Some(Value::StringType(name)) = inner.get("name") else {
+            panic!("expected \"name\" to round-trip as a string");
+        };
+        assert_eq!(name, "Half-Life");
+        let Some(Value::Int32Type(count)) = inner.get("count") else {
+            panic!("expected \"count\" to round-trip as an int32");
+        };
+        assert_eq!(*count, 42);
+    }
+
+    // Regression test for the `deserialize_option` fix: an `Option<T>` field
+    // whose key is present in the KeyValues tree must deserialize as `Some`,
+    // not get rejected by the default `forward_to_deserialize_any` behavior
+    // of treating every value as "maybe absent".
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct SampleWithOption {
+        name: String,
+        nickname: Option<String>,
+    }
+
+    #[test]
+    fn test_deserialize_option_present_value_is_some() {
+        let mut kv = KeyValue::new();
+        kv.insert("name".to_string(), Value::StringType("Steam".to_string()));
+        kv.insert("nickname".to_string(), Value::StringType("Valve".to_string()));
+        let value = Value::KeyValueType(kv);
+
+        let sample: SampleWithOption = de::Deserialize::deserialize(&value).unwrap();
+        assert_eq!(
+            sample,
+            SampleWithOption {
+                name: "Steam".to_string(),
+                nickname: Some("Valve".to_string()),
+            }
+        );
+    }
+
+    fn sample_app(key_values: KeyValue) -> App {
+        App {
+            id: 400,
+            size: 0,
+            state: 0,
+            last_update: 0,
+            access_token: 0,
+            checksum_txt: sha1_digest(write_text_keyvalues(&key_values).as_bytes()),
+            checksum_bin: None,
+            change_number: 0,
+            key_values,
+        }
+    }
+
+    #[test]
+    fn test_app_verify_passes_for_untampered_payload() {
+        let mut kv = KeyValue::new();
+        kv.insert("name".to_string(), Value::StringType("Portal".to_string()));
+
+        assert!(sample_app(kv).verify().is_ok());
+    }
+
+    #[test]
+    fn test_app_verify_fails_for_tampered_payload() {
+        let mut kv = KeyValue::new();
+        kv.insert("name".to_string(), Value::StringType("Portal".to_string()));
+
+        let mut app = sample_app(kv);
+        app.key_values.insert("name".to_string(), Value::StringType("Tampered".to_string()));
+
+        assert!(matches!(app.verify(), Err(VdfrError::ChecksumMismatch { app_id: 400, .. })));
+    }
+}